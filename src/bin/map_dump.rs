@@ -0,0 +1,81 @@
+//! Generates a level the same way the game does and prints it as a plain-text map, or reads a
+//! plain-text map and prints it back, to check a hand-made level loads as intended.
+//!
+//! Usage: `cargo run --bin map_dump [depth] [width height]`
+//!        `cargo run --bin map_dump -- --file <path> [depth]`
+extern crate hellorust;
+extern crate rltk;
+
+use hellorust::difficulty::Difficulty;
+use hellorust::map_builders::{self, BuilderChain, TextLevelBuilder};
+use hellorust::{HEIGHT, WIDTH};
+use rltk::RandomNumberGenerator;
+
+/// The smallest level every builder recipe can lay out.
+const MIN_WIDTH: i32 = 30;
+const MIN_HEIGHT: i32 = 20;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let file = if args.get(1).map(String::as_str) == Some("--file") {
+        Some(args.get(2).expect("--file needs a path").clone())
+    } else {
+        None
+    };
+    let skip = if file.is_some() { 2 } else { 0 };
+    let arg = |n: usize, default: i32| {
+        args.get(n + skip)
+            .and_then(|arg| arg.parse::<i32>().ok())
+            .unwrap_or(default)
+    };
+    let depth = arg(1, 1);
+    let width = arg(2, WIDTH as i32);
+    let height = arg(3, HEIGHT as i32);
+    if file.is_none() && (width < MIN_WIDTH || height < MIN_HEIGHT) {
+        eprintln!(
+            "Levels must be at least {}x{}, got {}x{}.\nUsage: map_dump [depth] [width height]",
+            MIN_WIDTH, MIN_HEIGHT, width, height
+        );
+        std::process::exit(1);
+    }
+
+    let mut rng = RandomNumberGenerator::new();
+    let builder = match file {
+        Some(path) => {
+            let text_level = TextLevelBuilder::from_file(&path).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            });
+            let mut builder = BuilderChain::new(depth, width, height);
+            builder.start_with(text_level).build_map(&mut rng);
+            builder
+        }
+        None => map_builders::build_valid_map(
+            depth,
+            width,
+            height,
+            Difficulty::default(),
+            &mut rng,
+            false,
+        ),
+    };
+    let build_data = &builder.build_data;
+    let map = &build_data.map;
+
+    println!(
+        "depth {}, {}x{}, {:?}",
+        map.depth, map.width, map.height, map.theme
+    );
+    let lines = map_builders::level_to_text(
+        map,
+        build_data.starting_position.as_ref(),
+        &build_data.spawn_list,
+    );
+    for line in lines {
+        println!("{}", line);
+    }
+    // On stderr, so the map on stdout can be loaded back with --file
+    for rejection in builder.rejections.iter() {
+        eprintln!("{}", rejection);
+    }
+}
@@ -0,0 +1,146 @@
+use rltk::{Point, Rltk};
+use specs::prelude::*;
+
+use super::{
+    memory_system::{is_visible, MEMORY_BRIGHTNESS},
+    tile_glyph, Item, LastSeen, Map, Monster, ParticleLifetime, Position, Renderable, SeesItems,
+    SeesMonsters, HEIGHT, WIDTH,
+};
+
+/// World coordinates of the top-left and bottom-right (exclusive) corners of the play area,
+/// keeping the player in the middle of the screen.
+pub fn get_screen_bounds(ecs: &World) -> (i32, i32, i32, i32) {
+    let player_pos = ecs.fetch::<Point>();
+    let (x_chars, y_chars) = (WIDTH as i32, HEIGHT as i32);
+
+    let min_x = player_pos.x - x_chars / 2;
+    let min_y = player_pos.y - y_chars / 2;
+    (min_x, min_x + x_chars, min_y, min_y + y_chars)
+}
+
+pub fn world_to_screen(ecs: &World, pos: Point) -> Point {
+    let (min_x, _, min_y, _) = get_screen_bounds(ecs);
+    Point::new(pos.x - min_x, pos.y - min_y)
+}
+
+pub fn screen_to_world(ecs: &World, pos: Point) -> Point {
+    let (min_x, _, min_y, _) = get_screen_bounds(ecs);
+    Point::new(pos.x + min_x, pos.y + min_y)
+}
+
+/// The map tile under the mouse, or `None` if the mouse is outside the play area or the map.
+pub fn mouse_world_pos(ecs: &World, ctx: &Rltk) -> Option<Point> {
+    let mouse_pos = ctx.mouse_pos();
+    if mouse_pos.0 < 0
+        || mouse_pos.0 >= WIDTH as i32
+        || mouse_pos.1 < 0
+        || mouse_pos.1 >= HEIGHT as i32
+    {
+        return None;
+    }
+    let world = screen_to_world(ecs, Point::new(mouse_pos.0, mouse_pos.1));
+    let map = ecs.fetch::<Map>();
+    if world.x < 0 || world.x >= map.width || world.y < 0 || world.y >= map.height {
+        return None;
+    }
+    Some(world)
+}
+
+pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs);
+    render_tiles(&map, ctx, (min_x, max_x, min_y, max_y));
+
+    let player_entity = ecs.fetch::<Entity>();
+    let sees_monsters = ecs.read_storage::<SeesMonsters>().contains(*player_entity);
+    let sees_items = ecs.read_storage::<SeesItems>().contains(*player_entity);
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let monsters = ecs.read_storage::<Monster>();
+    let items = ecs.read_storage::<Item>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    let last_seen = ecs.read_storage::<LastSeen>();
+    let is_detected = |entity: Entity| {
+        (sees_monsters && monsters.contains(entity)) || (sees_items && items.contains(entity))
+    };
+
+    // Remembered monsters and items go underneath anything actually in view
+    for (entity, memory) in (&entities, &last_seen).join() {
+        if memory.depth != map.depth
+            || memory.x < min_x
+            || memory.x >= max_x
+            || memory.y < min_y
+            || memory.y >= max_y
+        {
+            continue;
+        }
+        if is_visible(&map, positions.get(entity)) || is_detected(entity) {
+            continue;
+        }
+        ctx.set(
+            memory.x - min_x,
+            memory.y - min_y,
+            memory.fg.to_greyscale() * MEMORY_BRIGHTNESS,
+            rltk::RGB::named(rltk::BLACK),
+            memory.glyph,
+        )
+    }
+
+    let mut data = (&entities, &positions, &renderables)
+        .join()
+        .collect::<Vec<_>>();
+    data.sort_by_key(|e| std::cmp::Reverse(e.2.render_order));
+    for (entity, pos, render) in data.iter() {
+        if pos.x < min_x || pos.x >= max_x || pos.y < min_y || pos.y >= max_y {
+            continue;
+        }
+        let idx = map.xy_idxi32(pos.x, pos.y);
+        if map.visible_tiles[idx] || (particles.contains(*entity) && map.revealed_tiles[idx]) {
+            ctx.set(
+                pos.x - min_x,
+                pos.y - min_y,
+                render.fg,
+                render.bg,
+                render.glyph,
+            )
+        } else if is_detected(*entity) {
+            // Sensed but not seen, drawn greyed out like the remembered map
+            ctx.set(
+                pos.x - min_x,
+                pos.y - min_y,
+                render.fg.to_greyscale(),
+                rltk::RGB::named(rltk::BLACK),
+                render.glyph,
+            )
+        }
+    }
+}
+
+/// Draws a map generation snapshot, centred on the middle of the map rather than the player.
+pub fn render_snapshot(map: &Map, ctx: &mut Rltk) {
+    let min_x = map.width / 2 - WIDTH as i32 / 2;
+    let min_y = map.height / 2 - HEIGHT as i32 / 2;
+    render_tiles(
+        map,
+        ctx,
+        (min_x, min_x + WIDTH as i32, min_y, min_y + HEIGHT as i32),
+    );
+}
+
+/// Draws the revealed tiles inside the given world bounds, as returned by `get_screen_bounds`.
+fn render_tiles(map: &Map, ctx: &mut Rltk, bounds: (i32, i32, i32, i32)) {
+    let (min_x, max_x, min_y, max_y) = bounds;
+    for (screen_y, y) in (min_y..max_y).enumerate() {
+        for (screen_x, x) in (min_x..max_x).enumerate() {
+            if x < 0 || x >= map.width || y < 0 || y >= map.height {
+                continue;
+            }
+            let idx = map.xy_idxi32(x, y);
+            if map.revealed_tiles[idx] {
+                let (glyph, fg, bg) = tile_glyph(idx, map);
+                ctx.set(screen_x, screen_y, fg, bg, glyph);
+            }
+        }
+    }
+}
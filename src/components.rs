@@ -12,6 +12,14 @@ pub struct Position {
     pub y: i32,
 }
 
+/// Where an entity is on a level the player isn't on, used instead of a `Position`.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Renderable {
     pub glyph: rltk::FontCharType,
@@ -70,11 +78,6 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, ConvertSaveload, Debug, Clone)]
-pub struct SufferDamage {
-    pub amount: Vec<i32>,
-}
-
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct Item {}
 
@@ -102,8 +105,66 @@ pub struct AreaOfEffect {
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct CanTargetAnything {}
 
+/// Blasts from this item chip `damage` off every wall they hit, see `Map::damage_wall`.
+#[derive(Component, ConvertSaveload, Debug, Clone)]
+pub struct DestroysWalls {
+    pub damage: i32,
+}
+
+/// Lights up its surroundings, either where it stands or, when equipped, around its owner.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct LightSource {
+    pub color: RGB,
+    pub range: i32,
+}
+
+/// Climbing over rubble costs the entity its next turn.
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct SlowedByTerrain {}
+
+/// Reveals the map around the user, or the whole level when `radius` is `None`.
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
-pub struct DestroysWalls {}
+pub struct MagicMapper {
+    pub radius: Option<i32>,
+}
+
+/// Lets the user sense every monster on the level for `duration` turns.
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct DetectsMonsters {
+    pub duration: i32,
+}
+
+/// Lets the user sense every item lying on the level for `duration` turns.
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct DetectsItems {
+    pub duration: i32,
+}
+
+/// Monsters show up through walls, greyed out, while `turns` last.
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct SeesMonsters {
+    pub turns: i32,
+}
+
+/// Items on the floor show up through walls, greyed out, while `turns` last.
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct SeesItems {
+    pub turns: i32,
+}
+
+/// Where the player last saw a monster or item, and what it looked like, so that it can still
+/// be drawn once it's out of sight.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LastSeen {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+    pub glyph: rltk::FontCharType,
+    pub fg: RGB,
+    pub turns_ago: i32,
+    /// Set once the remembered tile has left view, so that seeing it again clears the memory.
+    pub tile_out_of_view: bool,
+}
 
 #[derive(Component, ConvertSaveload, Debug, Clone)]
 pub struct Confusion {
@@ -133,10 +194,16 @@ pub struct ProvidesHealing {
     pub healing_amount: i32,
 }
 
+#[derive(Component, ConvertSaveload, Debug, Clone)]
+pub struct ProvidesMana {
+    pub mana_amount: i32,
+}
+
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Light,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -168,6 +235,10 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    pub wizard: super::wizard::WizardMode,
+    pub dungeon_master: super::dungeon::DungeonMaster,
+    #[serde(default)]
+    pub difficulty: super::difficulty::Difficulty,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -206,16 +277,3 @@ pub struct Pierces {}
 pub struct KnownSpells {
     pub spells: Vec<KnownSpell>,
 }
-
-impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
-        if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
-        } else {
-            let dmg = SufferDamage {
-                amount: vec![amount],
-            };
-            store.insert(victim, dmg).expect("Unable to insert damage");
-        }
-    }
-}
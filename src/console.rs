@@ -0,0 +1,692 @@
+use rltk::{Point, Rltk, VirtualKeyCode, RGB};
+use specs::prelude::*;
+
+use super::{
+    map_builders::level_to_text, spawner, spells, wizard, CombatStats, InBackpack, Item,
+    MagicStats, Map, MapGenVisualizer, Name, Player, Position, WINDOW_WIDTH,
+};
+
+const CONSOLE_HEIGHT: usize = 20;
+const MAX_OUTPUT_LINES: usize = 200;
+
+const COMMANDS: [&str; 12] = [
+    "clear", "dump", "give", "god", "help", "learn", "list", "mapgen", "reveal", "set", "spawn",
+    "teleport",
+];
+const STATS: [&str; 6] = ["defense", "hp", "mana", "max_hp", "max_mana", "power"];
+
+/// Drop-down developer console, toggled with the backtick key. Everything it prints goes to
+/// `output` rather than the `GameLog`.
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: Vec<String>,
+    history: Vec<String>,
+    history_cursor: Option<usize>,
+}
+
+impl Console {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Console {
+        Console {
+            open: false,
+            input: String::new(),
+            output: vec!["Type 'help' for a list of commands.".to_string()],
+            history: Vec::new(),
+            history_cursor: None,
+        }
+    }
+
+    fn print<S: ToString>(&mut self, line: S) {
+        self.output.push(line.to_string());
+        if self.output.len() > MAX_OUTPUT_LINES {
+            let excess = self.output.len() - MAX_OUTPUT_LINES;
+            self.output.drain(0..excess);
+        }
+    }
+
+    fn history_step(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let cursor = match (self.history_cursor, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i + 1 < self.history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.history_cursor = cursor;
+        self.input = match cursor {
+            Some(i) => self.history[i].clone(),
+            None => String::new(),
+        };
+    }
+
+    fn complete(&mut self) {
+        let (prefix, candidates) = completion_candidates(&self.input);
+        match candidates.len() {
+            0 => {}
+            1 => self.input = format!("{}{} ", prefix, candidates[0]),
+            _ => {
+                self.input = format!("{}{}", prefix, longest_common_prefix(&candidates));
+                self.print(candidates.join("  "));
+            }
+        }
+    }
+}
+
+/// Handles console input for this frame. Returns true while the console is open, in which case
+/// the caller should not pass the frame's input on to the game.
+pub fn console_input(ecs: &mut World, ctx: &Rltk) -> bool {
+    let key = match ctx.key {
+        None => return ecs.fetch::<Console>().open,
+        Some(key) => key,
+    };
+
+    let command;
+    {
+        let mut console = ecs.fetch_mut::<Console>();
+        if key == VirtualKeyCode::Grave {
+            console.open = !console.open;
+            return true;
+        }
+        if !console.open {
+            return false;
+        }
+
+        match key {
+            VirtualKeyCode::Escape => console.open = false,
+            VirtualKeyCode::Back => {
+                console.input.pop();
+            }
+            VirtualKeyCode::Up => console.history_step(true),
+            VirtualKeyCode::Down => console.history_step(false),
+            VirtualKeyCode::Tab => console.complete(),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {}
+            _ => {
+                if let Some(c) = key_to_char(key, ctx.shift) {
+                    console.input.push(c);
+                }
+            }
+        }
+        if key != VirtualKeyCode::Return && key != VirtualKeyCode::NumpadEnter {
+            return true;
+        }
+
+        command = console.input.trim().to_string();
+        console.input.clear();
+        console.history_cursor = None;
+        if command.is_empty() {
+            return true;
+        }
+        console.history.push(command.clone());
+        console.print(format!("> {}", command));
+        if command == "clear" {
+            console.output.clear();
+            return true;
+        }
+    }
+
+    let lines = run_command(ecs, &command);
+    let mut console = ecs.fetch_mut::<Console>();
+    for line in lines {
+        console.print(line);
+    }
+    true
+}
+
+pub fn draw_console(ecs: &World, ctx: &mut Rltk) {
+    let console = ecs.fetch::<Console>();
+    if !console.open {
+        return;
+    }
+
+    ctx.draw_box(
+        0,
+        0,
+        WINDOW_WIDTH - 1,
+        CONSOLE_HEIGHT,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+
+    let visible_lines = CONSOLE_HEIGHT - 2;
+    let first = console.output.len().saturating_sub(visible_lines);
+    for (i, line) in console.output[first..].iter().enumerate() {
+        let clipped: String = line.chars().take(WINDOW_WIDTH - 2).collect();
+        ctx.print_color(
+            1,
+            1 + i,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            clipped,
+        );
+    }
+    ctx.print_color(
+        1,
+        CONSOLE_HEIGHT - 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!("> {}_", console.input),
+    );
+}
+
+fn run_command(ecs: &mut World, line: &str) -> Vec<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    // Anything that changes the game counts as cheating
+    if let Some(command) = words.first() {
+        if matches!(
+            *command,
+            "spawn" | "give" | "set" | "reveal" | "god" | "teleport" | "learn"
+        ) {
+            ecs.write_resource::<wizard::WizardMode>().used = true;
+        }
+    }
+
+    match words.as_slice() {
+        ["help"] => vec![
+            "spawn <name> [x y]   spawn a monster or item, at the player by default".to_string(),
+            "give <item>          put an item in the player's backpack".to_string(),
+            format!("set <stat> <value>   stats: {}", STATS.join(", ")),
+            "list entities [here] list named entities, optionally on the player's tile".to_string(),
+            "dump map             print the current level as a plain-text map".to_string(),
+            "teleport <x> <y>     move the player".to_string(),
+            "learn <spell>        learn a spell".to_string(),
+            "mapgen [ms]          toggle replaying level generation, or set its speed".to_string(),
+            "mapgen log           list levels thrown away while building this one".to_string(),
+            "reveal, god, clear".to_string(),
+        ],
+        ["spawn", args @ ..] if !args.is_empty() => spawn_command(ecs, args),
+        ["give", args @ ..] if !args.is_empty() => give_command(ecs, &args.join(" ")),
+        ["set", stat, value] => set_command(ecs, stat, value),
+        ["list", "entities"] => list_entities(ecs, false),
+        ["list", "entities", "here"] => list_entities(ecs, true),
+        ["dump", "map"] => dump_map(ecs),
+        ["mapgen"] => {
+            let mut visualizer = ecs.write_resource::<MapGenVisualizer>();
+            visualizer.enabled = !visualizer.enabled;
+            if visualizer.enabled {
+                vec!["Map generation will be shown for new levels.".to_string()]
+            } else {
+                vec!["Map generation will not be shown.".to_string()]
+            }
+        }
+        ["mapgen", "log"] => {
+            let rejections = &ecs.fetch::<MapGenVisualizer>().rejections;
+            if rejections.is_empty() {
+                vec!["This level was built on the first attempt.".to_string()]
+            } else {
+                rejections.clone()
+            }
+        }
+        ["mapgen", ms] => match ms.parse::<f32>() {
+            Ok(ms) if ms > 0.0 => {
+                ecs.write_resource::<MapGenVisualizer>().frame_time = ms;
+                vec![format!("Map generation snapshots last {}ms.", ms)]
+            }
+            _ => vec!["Usage: mapgen [ms]".to_string()],
+        },
+        ["reveal"] => {
+            wizard::reveal_map(ecs);
+            vec!["Map revealed.".to_string()]
+        }
+        ["god"] => {
+            if wizard::toggle_god_mode(ecs) {
+                vec!["God mode enabled.".to_string()]
+            } else {
+                vec!["God mode disabled.".to_string()]
+            }
+        }
+        ["teleport", x, y] => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) => {
+                if wizard::teleport_player(ecs, Point::new(x, y)) {
+                    vec![format!("Teleported to {},{}.", x, y)]
+                } else {
+                    vec![format!("Can't teleport to {},{}.", x, y)]
+                }
+            }
+            _ => vec!["Usage: teleport <x> <y>".to_string()],
+        },
+        ["learn", args @ ..] if !args.is_empty() => {
+            let name = args.join(" ").to_lowercase();
+            match wizard::grant_spell(ecs, &name) {
+                wizard::GrantSpellResult::Learned => vec![format!("Learned {}.", name)],
+                wizard::GrantSpellResult::AlreadyKnown => {
+                    vec![format!("{} is already known.", name)]
+                }
+                wizard::GrantSpellResult::NoSuchSpell => vec![format!("No spell called {}.", name)],
+            }
+        }
+        _ => vec![format!("Unknown command: {}", line)],
+    }
+}
+
+fn find_spawnable(name: &str) -> Option<&'static str> {
+    spawner::SPAWNABLE_NAMES
+        .iter()
+        .find(|n| n.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+fn spawn_command(ecs: &mut World, args: &[&str]) -> Vec<String> {
+    // Trailing coordinates are optional, names may contain spaces
+    let (name_words, target) = match args {
+        [name @ .., x, y] if !name.is_empty() => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) => (name, Some(Point::new(x, y))),
+            _ => (args, None),
+        },
+        _ => (args, None),
+    };
+    let target = target.unwrap_or_else(|| *ecs.fetch::<Point>());
+    let name = name_words.join(" ");
+
+    let spawnable = match find_spawnable(&name) {
+        None => return vec![format!("Nothing called {} can be spawned.", name)],
+        Some(spawnable) => spawnable,
+    };
+    {
+        let map = ecs.fetch::<Map>();
+        if target.x < 0 || target.x >= map.width || target.y < 0 || target.y >= map.height {
+            return vec![format!("{},{} is outside the map.", target.x, target.y)];
+        }
+    }
+    wizard::spawn_at(ecs, spawnable, target);
+    vec![format!(
+        "Spawned {} at {},{}.",
+        spawnable, target.x, target.y
+    )]
+}
+
+fn give_command(ecs: &mut World, name: &str) -> Vec<String> {
+    let spawnable = match find_spawnable(name) {
+        None => return vec![format!("Nothing called {} can be spawned.", name)],
+        Some(spawnable) => spawnable,
+    };
+    let player_pos = *ecs.fetch::<Point>();
+    let player_entity = *ecs.fetch::<Entity>();
+    let item = match spawner::spawn_named_entity(ecs, spawnable, player_pos.x, player_pos.y) {
+        None => return vec![format!("Nothing called {} can be spawned.", name)],
+        Some(item) => item,
+    };
+
+    if ecs.read_storage::<Item>().get(item).is_none() {
+        ecs.delete_entity(item).expect("Unable to delete entity");
+        return vec![format!("{} is not an item.", spawnable)];
+    }
+    ecs.write_storage::<Position>().remove(item);
+    ecs.write_storage::<InBackpack>()
+        .insert(
+            item,
+            InBackpack {
+                owner: player_entity,
+            },
+        )
+        .expect("Unable to insert backpack entry");
+    vec![format!("Gave the player a {}.", spawnable)]
+}
+
+fn set_command(ecs: &mut World, stat: &str, value: &str) -> Vec<String> {
+    let value = match value.parse::<i32>() {
+        Ok(value) => value,
+        Err(_) => return vec![format!("{} is not a number.", value)],
+    };
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut magic_stats = ecs.write_storage::<MagicStats>();
+
+    match stat {
+        "hp" | "max_hp" | "power" | "defense" => {
+            if let Some(stats) = combat_stats.get_mut(player_entity) {
+                match stat {
+                    "hp" => {
+                        stats.hp = value;
+                        stats.max_hp = i32::max(stats.max_hp, value);
+                    }
+                    "max_hp" => stats.max_hp = value,
+                    "power" => stats.power = value,
+                    _ => stats.defense = value,
+                }
+            }
+        }
+        "mana" | "max_mana" => {
+            if let Some(stats) = magic_stats.get_mut(player_entity) {
+                if stat == "mana" {
+                    stats.mana = value;
+                    stats.max_mana = i32::max(stats.max_mana, value);
+                } else {
+                    stats.max_mana = value;
+                }
+            }
+        }
+        _ => {
+            return vec![format!(
+                "Unknown stat {}, try one of: {}",
+                stat,
+                STATS.join(", ")
+            )]
+        }
+    }
+    vec![format!("Set {} to {}.", stat, value)]
+}
+
+fn list_entities(ecs: &World, here_only: bool) -> Vec<String> {
+    let entities = ecs.entities();
+    let names = ecs.read_storage::<Name>();
+    let positions = ecs.read_storage::<Position>();
+    let player_pos = ecs.fetch::<Point>();
+
+    let mut lines = Vec::new();
+    for (entity, name, pos) in (&entities, &names, &positions).join() {
+        if here_only && (pos.x != player_pos.x || pos.y != player_pos.y) {
+            continue;
+        }
+        lines.push(format!(
+            "#{:<4} {:<22} {},{}",
+            entity.id(),
+            name.name,
+            pos.x,
+            pos.y
+        ));
+    }
+    if lines.is_empty() {
+        lines.push("No entities found.".to_string());
+    }
+    lines
+}
+
+fn dump_map(ecs: &World) -> Vec<String> {
+    let map = ecs.fetch::<Map>();
+    let player_pos = ecs.fetch::<Point>();
+    let names = ecs.read_storage::<Name>();
+    let positions = ecs.read_storage::<Position>();
+    let players = ecs.read_storage::<Player>();
+
+    let spawns: Vec<(usize, String)> = (&names, &positions, !&players)
+        .join()
+        .map(|(name, pos, _player)| (map.xy_idxi32(pos.x, pos.y), name.name.clone()))
+        .collect();
+    let start = Position {
+        x: player_pos.x,
+        y: player_pos.y,
+    };
+    level_to_text(&map, Some(&start), &spawns)
+}
+
+/// Returns the part of `input` that is kept as-is, and the possible completions for the rest.
+fn completion_candidates(input: &str) -> (String, Vec<String>) {
+    let lowered = input.to_lowercase();
+    let (command, argument) = match lowered.find(' ') {
+        None => {
+            let candidates = COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(lowered.as_str()))
+                .map(|c| c.to_string())
+                .collect();
+            return (String::new(), candidates);
+        }
+        Some(space) => (&lowered[..space], lowered[space + 1..].trim_start()),
+    };
+
+    let options: Vec<String> = match command {
+        "spawn" | "give" => spawner::SPAWNABLE_NAMES
+            .iter()
+            .map(|n| n.to_lowercase())
+            .collect(),
+        "learn" => spells::SPELL_NAMES.iter().map(|n| n.to_string()).collect(),
+        "set" => STATS.iter().map(|n| n.to_string()).collect(),
+        "list" => vec!["entities".to_string(), "entities here".to_string()],
+        "dump" => vec!["map".to_string()],
+        _ => Vec::new(),
+    };
+    let candidates = options
+        .into_iter()
+        .filter(|o| o.starts_with(argument))
+        .collect();
+    (format!("{} ", command), candidates)
+}
+
+fn longest_common_prefix(candidates: &[String]) -> String {
+    let mut prefix = candidates[0].clone();
+    for candidate in candidates.iter().skip(1) {
+        while !candidate.starts_with(prefix.as_str()) {
+            prefix.pop();
+        }
+    }
+    prefix
+}
+
+fn key_to_char(key: VirtualKeyCode, shift: bool) -> Option<char> {
+    const LETTERS: [VirtualKeyCode; 26] = [
+        VirtualKeyCode::A,
+        VirtualKeyCode::B,
+        VirtualKeyCode::C,
+        VirtualKeyCode::D,
+        VirtualKeyCode::E,
+        VirtualKeyCode::F,
+        VirtualKeyCode::G,
+        VirtualKeyCode::H,
+        VirtualKeyCode::I,
+        VirtualKeyCode::J,
+        VirtualKeyCode::K,
+        VirtualKeyCode::L,
+        VirtualKeyCode::M,
+        VirtualKeyCode::N,
+        VirtualKeyCode::O,
+        VirtualKeyCode::P,
+        VirtualKeyCode::Q,
+        VirtualKeyCode::R,
+        VirtualKeyCode::S,
+        VirtualKeyCode::T,
+        VirtualKeyCode::U,
+        VirtualKeyCode::V,
+        VirtualKeyCode::W,
+        VirtualKeyCode::X,
+        VirtualKeyCode::Y,
+        VirtualKeyCode::Z,
+    ];
+    const DIGITS: [VirtualKeyCode; 10] = [
+        VirtualKeyCode::Key0,
+        VirtualKeyCode::Key1,
+        VirtualKeyCode::Key2,
+        VirtualKeyCode::Key3,
+        VirtualKeyCode::Key4,
+        VirtualKeyCode::Key5,
+        VirtualKeyCode::Key6,
+        VirtualKeyCode::Key7,
+        VirtualKeyCode::Key8,
+        VirtualKeyCode::Key9,
+    ];
+
+    if let Some(i) = LETTERS.iter().position(|k| *k == key) {
+        let c = (b'a' + i as u8) as char;
+        return Some(if shift { c.to_ascii_uppercase() } else { c });
+    }
+    if let Some(i) = DIGITS.iter().position(|k| *k == key) {
+        return Some((b'0' + i as u8) as char);
+    }
+    match key {
+        VirtualKeyCode::Space => Some(' '),
+        VirtualKeyCode::Minus if shift => Some('_'),
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Some('-'),
+        VirtualKeyCode::Period => Some('.'),
+        VirtualKeyCode::Comma => Some(','),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{register_components, SerializeMe};
+    use specs::saveload::SimpleMarkerAllocator;
+
+    /// Just enough of the game's resources for the commands, with the player at 5,5.
+    fn world() -> World {
+        let mut ecs = World::new();
+        register_components(&mut ecs);
+        ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        ecs.insert(Map::new(1, 10, 10));
+        ecs.insert(Point::new(5, 5));
+        ecs.insert(wizard::WizardMode::default());
+        ecs.insert(MapGenVisualizer {
+            enabled: false,
+            frame_time: 100.0,
+            rejections: Vec::new(),
+        });
+        let player = spawner::player(&mut ecs, 5, 5);
+        ecs.insert(player);
+        ecs
+    }
+
+    fn named(ecs: &World, name: &str) -> Vec<(i32, i32)> {
+        let names = ecs.read_storage::<Name>();
+        let positions = ecs.read_storage::<Position>();
+        (&names, &positions)
+            .join()
+            .filter(|(n, _pos)| n.name == name)
+            .map(|(_n, pos)| (pos.x, pos.y))
+            .collect()
+    }
+
+    #[test]
+    fn spawn_takes_optional_coordinates() {
+        let mut ecs = world();
+        assert_eq!(
+            run_command(&mut ecs, "spawn orc 3 4"),
+            vec!["Spawned Orc at 3,4."]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "spawn health potion"),
+            vec!["Spawned Health Potion at 5,5."]
+        );
+        assert_eq!(named(&ecs, "Orc"), vec![(3, 4)]);
+        assert_eq!(named(&ecs, "Health Potion"), vec![(5, 5)]);
+        assert!(ecs.fetch::<wizard::WizardMode>().used);
+    }
+
+    #[test]
+    fn malformed_spawns_are_refused() {
+        let mut ecs = world();
+        assert_eq!(
+            run_command(&mut ecs, "spawn orc x"),
+            vec!["Nothing called orc x can be spawned."]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "spawn dragon"),
+            vec!["Nothing called dragon can be spawned."]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "spawn orc 30 4"),
+            vec!["30,4 is outside the map."]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "spawn"),
+            vec!["Unknown command: spawn"]
+        );
+        assert!(named(&ecs, "Orc").is_empty());
+    }
+
+    #[test]
+    fn set_checks_its_arguments() {
+        let mut ecs = world();
+        assert_eq!(
+            run_command(&mut ecs, "set hp"),
+            vec!["Unknown command: set hp"]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "set hp lots"),
+            vec!["lots is not a number."]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "set luck 3"),
+            vec![format!(
+                "Unknown stat luck, try one of: {}",
+                STATS.join(", ")
+            )]
+        );
+        assert_eq!(run_command(&mut ecs, "set hp 50"), vec!["Set hp to 50."]);
+
+        let player = *ecs.fetch::<Entity>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let stats = combat_stats.get(player).unwrap();
+        assert_eq!((stats.hp, stats.max_hp), (50, 50));
+    }
+
+    #[test]
+    fn unknown_and_malformed_commands() {
+        let mut ecs = world();
+        assert_eq!(
+            run_command(&mut ecs, "frobnicate now"),
+            vec!["Unknown command: frobnicate now"]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "mapgen 0"),
+            vec!["Usage: mapgen [ms]"]
+        );
+        assert_eq!(
+            run_command(&mut ecs, "mapgen 50"),
+            vec!["Map generation snapshots last 50ms."]
+        );
+        assert_eq!(ecs.fetch::<MapGenVisualizer>().frame_time, 50.0);
+        assert!(!ecs.fetch::<wizard::WizardMode>().used);
+
+        // Cheats are flagged even when the arguments are wrong
+        assert_eq!(
+            run_command(&mut ecs, "teleport 1 north"),
+            vec!["Usage: teleport <x> <y>"]
+        );
+        assert!(ecs.fetch::<wizard::WizardMode>().used);
+    }
+
+    #[test]
+    fn commands_complete_from_their_prefix() {
+        assert_eq!(
+            completion_candidates("te"),
+            (String::new(), vec!["teleport".to_string()])
+        );
+        assert_eq!(completion_candidates("").1.len(), COMMANDS.len());
+        assert_eq!(completion_candidates("x"), (String::new(), Vec::new()));
+    }
+
+    #[test]
+    fn arguments_complete_per_command() {
+        assert_eq!(
+            completion_candidates("SPAWN gob"),
+            ("spawn ".to_string(), vec!["goblin".to_string()])
+        );
+        assert_eq!(
+            completion_candidates("set max"),
+            (
+                "set ".to_string(),
+                vec!["max_hp".to_string(), "max_mana".to_string()]
+            )
+        );
+        assert_eq!(
+            completion_candidates("teleport 1"),
+            ("teleport ".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn tab_fills_in_as_much_as_is_shared() {
+        let mut console = Console::new();
+        console.input = "tel".to_string();
+        console.complete();
+        assert_eq!(console.input, "teleport ");
+
+        console.input = "spawn magic".to_string();
+        console.complete();
+        assert_eq!(console.input, "spawn magic m");
+        assert_eq!(
+            console.output.last().unwrap(),
+            "magic missile scroll  magic mapping scroll"
+        );
+
+        console.input = "nonsense".to_string();
+        console.complete();
+        assert_eq!(console.input, "nonsense");
+    }
+}
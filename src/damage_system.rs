@@ -1,33 +1,6 @@
 use specs::prelude::*;
 
-use super::{CombatStats, GameLog, Map, Name, Player, Position, RunState, SufferDamage};
-
-pub struct DamageSystem {}
-
-impl<'a> System<'a> for DamageSystem {
-    type SystemData = (
-        WriteStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
-        ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
-        Entities<'a>,
-    );
-
-    fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
-
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
-            let pos = positions.get(entity);
-            if let Some(pos) = pos {
-                let idx = map.xy_idxi32(pos.x, pos.y);
-                map.bloodstains.insert(idx);
-            }
-        }
-
-        damage.clear();
-    }
-}
+use super::{CombatStats, GameLog, Name, Player, RunState};
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
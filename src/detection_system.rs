@@ -0,0 +1,39 @@
+use specs::prelude::*;
+
+use super::{SeesItems, SeesMonsters};
+
+/// Wears off telepathy and item detection, one turn at a time.
+pub struct DetectionSystem {}
+
+impl<'a> System<'a> for DetectionSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, SeesMonsters>,
+        WriteStorage<'a, SeesItems>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut sees_monsters, mut sees_items) = data;
+
+        let mut expired: Vec<Entity> = Vec::new();
+        for (entity, status) in (&entities, &mut sees_monsters).join() {
+            status.turns -= 1;
+            if status.turns < 1 {
+                expired.push(entity);
+            }
+        }
+        for entity in expired.drain(..) {
+            sees_monsters.remove(entity);
+        }
+
+        for (entity, status) in (&entities, &mut sees_items).join() {
+            status.turns -= 1;
+            if status.turns < 1 {
+                expired.push(entity);
+            }
+        }
+        for entity in expired.drain(..) {
+            sees_items.remove(entity);
+        }
+    }
+}
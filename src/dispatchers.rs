@@ -0,0 +1,56 @@
+use specs::prelude::*;
+
+use super::detection_system::DetectionSystem;
+use super::effects::EffectSystem;
+use super::inventory_system::{
+    ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem,
+};
+use super::lighting_system::LightingSystem;
+use super::map_indexing_system::MapIndexingSystem;
+use super::melee_combat_system::MeleeCombatSystem;
+use super::memory_system::MemorySystem;
+use super::monster_ai_systems::MonsterAI;
+use super::particle_system::ParticleSpawnSystem;
+use super::VisibilitySystem;
+
+/// Systems run once before the player gets control, e.g. after loading or changing level.
+pub fn pre_run_dispatcher() -> Dispatcher<'static, 'static> {
+    DispatcherBuilder::new()
+        .with(MapIndexingSystem {}, "map_indexing", &[])
+        .with(LightingSystem {}, "lighting", &[])
+        .with(VisibilitySystem {}, "visibility", &["lighting"])
+        .with(ParticleSpawnSystem {}, "particles", &[])
+        .build()
+}
+
+/// Resolves the player's intents: attacking, picking up, using, dropping and removing items.
+pub fn player_turn_dispatcher() -> Dispatcher<'static, 'static> {
+    DispatcherBuilder::new()
+        .with(MapIndexingSystem {}, "map_indexing", &[])
+        .with(MeleeCombatSystem {}, "melee", &["map_indexing"])
+        .with(ItemUseSystem {}, "item_use", &["map_indexing"])
+        .with(ItemCollectionSystem {}, "pickup", &[])
+        .with(ItemDropSystem {}, "drop", &[])
+        .with(ItemRemoveSystem {}, "remove", &[])
+        .with(EffectSystem {}, "effects", &["melee", "item_use"])
+        .with(LightingSystem {}, "lighting", &["effects"])
+        .with(VisibilitySystem {}, "visibility", &["lighting"])
+        .with(ParticleSpawnSystem {}, "particles", &["effects"])
+        .build()
+}
+
+/// Lets every monster act, then resolves their attacks. Timed statuses and memories of what
+/// the player has seen age here too.
+pub fn monster_turn_dispatcher() -> Dispatcher<'static, 'static> {
+    DispatcherBuilder::new()
+        .with(MonsterAI {}, "monster_ai", &[])
+        .with(DetectionSystem {}, "detection", &[])
+        .with(MapIndexingSystem {}, "map_indexing", &["monster_ai"])
+        .with(LightingSystem {}, "lighting", &["monster_ai"])
+        .with(VisibilitySystem {}, "visibility", &["lighting"])
+        .with(MemorySystem {}, "memory", &["visibility"])
+        .with(MeleeCombatSystem {}, "melee", &["map_indexing"])
+        .with(EffectSystem {}, "effects", &["melee"])
+        .with(ParticleSpawnSystem {}, "particles", &["effects"])
+        .build()
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::{Map, OtherLevelPosition, Player, Position, Viewshed};
+
+/// Every level the player has left, kept so that going back finds it the way it was. Entities
+/// on those levels stay in the world with an `OtherLevelPosition` instead of a `Position`.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct DungeonMaster {
+    maps: HashMap<i32, Map>,
+}
+
+impl DungeonMaster {
+    pub fn store_map(&mut self, map: &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        self.maps.get(&depth).cloned()
+    }
+}
+
+/// Takes everything but the player off the current level, remembering where it was.
+pub fn freeze_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let players = ecs.read_storage::<Player>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete: Vec<Entity> = Vec::new();
+    for (entity, pos, _player) in (&entities, &positions, !&players).join() {
+        other_level_positions
+            .insert(
+                entity,
+                OtherLevelPosition {
+                    x: pos.x,
+                    y: pos.y,
+                    depth,
+                },
+            )
+            .expect("Insert fail");
+        pos_to_delete.push(entity);
+    }
+    for entity in pos_to_delete.iter() {
+        positions.remove(*entity);
+    }
+}
+
+/// Puts everything that was left on the current level back where it was.
+pub fn thaw_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete: Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &other_level_positions).join() {
+        if pos.depth == depth {
+            positions
+                .insert(entity, Position { x: pos.x, y: pos.y })
+                .expect("Insert fail");
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+            pos_to_delete.push(entity);
+        }
+    }
+    for entity in pos_to_delete.iter() {
+        other_level_positions.remove(*entity);
+    }
+}
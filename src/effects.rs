@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+
+use rltk::RGB;
+use specs::prelude::*;
+
+use super::{
+    particle_system::ParticleBuilder, wizard::WizardMode, CombatStats, Confusion, MagicStats, Map,
+    Player, Position, SeesItems, SeesMonsters,
+};
+
+/// Magic mapping lights up the newly revealed tiles near the centre for a moment, lingering
+/// longer the further the tile is from the centre, so that the map appears to sweep outwards.
+/// Tiles beyond `REVEAL_SWEEP_RADIUS` are revealed without one, to keep the particle count down.
+const REVEAL_SWEEP_BASE_MS: f32 = 150.0;
+const REVEAL_SWEEP_MS_PER_TILE: f32 = 20.0;
+const REVEAL_SWEEP_RADIUS: f32 = 10.0;
+
+pub enum EffectType {
+    Damage {
+        amount: i32,
+    },
+    Healing {
+        amount: i32,
+    },
+    RestoreMana {
+        amount: i32,
+    },
+    Confusion {
+        turns: i32,
+    },
+    Particle {
+        glyph: rltk::FontCharType,
+        fg: RGB,
+        bg: RGB,
+        lifetime: f32,
+    },
+    DamageTerrain {
+        amount: i32,
+    },
+    RevealMap {
+        radius: Option<i32>,
+    },
+    DetectMonsters {
+        turns: i32,
+    },
+    DetectItems {
+        turns: i32,
+    },
+}
+
+/// What an effect applies to. Tile targets hit every entity standing on them, or the tiles
+/// themselves for tile effects such as `DamageTerrain`.
+#[derive(Clone)]
+pub enum Targets {
+    Single { target: Entity },
+    TargetList { targets: Vec<Entity> },
+    Tiles { tiles: Vec<usize> },
+}
+
+pub struct EffectSpawner {
+    pub creator: Option<Entity>,
+    pub effect_type: EffectType,
+    pub targets: Targets,
+}
+
+/// Every damage, healing, status and particle request goes through this queue and is resolved,
+/// in the order it was added, by the `EffectSystem`.
+pub struct EffectQueue {
+    queue: VecDeque<EffectSpawner>,
+}
+
+impl EffectQueue {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> EffectQueue {
+        EffectQueue {
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn add_effect(
+        &mut self,
+        creator: Option<Entity>,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.queue.push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+        });
+    }
+}
+
+pub struct EffectSystem {}
+
+impl<'a> System<'a> for EffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, Map>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadExpect<'a, WizardMode>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, MagicStats>,
+        WriteStorage<'a, Confusion>,
+        WriteStorage<'a, SeesMonsters>,
+        WriteStorage<'a, SeesItems>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Player>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut queue,
+            mut map,
+            mut particle_builder,
+            wizard,
+            mut combat_stats,
+            mut magic_stats,
+            mut confused,
+            mut sees_monsters,
+            mut sees_items,
+            positions,
+            players,
+        ) = data;
+
+        while let Some(effect) = queue.queue.pop_front() {
+            match &effect.effect_type {
+                EffectType::DamageTerrain { amount } => {
+                    for tile_idx in target_tiles(&effect.targets, &map, &positions) {
+                        map.damage_wall(tile_idx, *amount);
+                    }
+                }
+                EffectType::RevealMap { radius } => {
+                    for centre_idx in target_tiles(&effect.targets, &map, &positions) {
+                        let (cx, cy) = map.idx_to_xy(centre_idx);
+                        let centre = rltk::Point::new(cx, cy);
+                        for idx in 0..map.tile_count() {
+                            let (x, y) = map.idx_to_xy(idx);
+                            let distance = rltk::DistanceAlg::Pythagoras
+                                .distance2d(centre, rltk::Point::new(x, y));
+                            if radius.is_some_and(|radius| distance > radius as f32) {
+                                continue;
+                            }
+                            if map.revealed_tiles[idx] {
+                                continue;
+                            }
+                            map.revealed_tiles[idx] = true;
+                            if distance <= REVEAL_SWEEP_RADIUS {
+                                particle_builder.request_unlit(
+                                    x,
+                                    y,
+                                    RGB::named(rltk::CYAN),
+                                    RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('░'),
+                                    REVEAL_SWEEP_BASE_MS + distance * REVEAL_SWEEP_MS_PER_TILE,
+                                );
+                            }
+                        }
+                    }
+                }
+                EffectType::DetectMonsters { turns } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        let turns = sees_monsters
+                            .get(target)
+                            .map_or(*turns, |status| i32::max(status.turns, *turns));
+                        sees_monsters
+                            .insert(target, SeesMonsters { turns })
+                            .expect("Unable to insert status");
+                    }
+                }
+                EffectType::DetectItems { turns } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        let turns = sees_items
+                            .get(target)
+                            .map_or(*turns, |status| i32::max(status.turns, *turns));
+                        sees_items
+                            .insert(target, SeesItems { turns })
+                            .expect("Unable to insert status");
+                    }
+                }
+                EffectType::Particle {
+                    glyph,
+                    fg,
+                    bg,
+                    lifetime,
+                } => {
+                    for tile_idx in target_tiles(&effect.targets, &map, &positions) {
+                        let (x, y) = map.idx_to_xy(tile_idx);
+                        particle_builder.request(x, y, *fg, *bg, *glyph, *lifetime);
+                    }
+                }
+                EffectType::Damage { amount } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        if wizard.god_mode && players.get(target).is_some() {
+                            continue;
+                        }
+                        if let Some(stats) = combat_stats.get_mut(target) {
+                            stats.hp -= amount;
+                            if let Some(pos) = positions.get(target) {
+                                let idx = map.xy_idxi32(pos.x, pos.y);
+                                map.bloodstains.insert(idx);
+                            }
+                        }
+                    }
+                }
+                EffectType::Healing { amount } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        if let Some(stats) = combat_stats.get_mut(target) {
+                            stats.hp = i32::min(stats.max_hp, stats.hp + amount);
+                        }
+                    }
+                }
+                EffectType::RestoreMana { amount } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        if let Some(stats) = magic_stats.get_mut(target) {
+                            stats.mana = i32::min(stats.max_mana, stats.mana + amount);
+                        }
+                    }
+                }
+                EffectType::Confusion { turns } => {
+                    for target in target_entities(&effect.targets, &map) {
+                        if combat_stats.get(target).is_some() {
+                            confused
+                                .insert(target, Confusion { duration: *turns })
+                                .expect("Unable to insert status");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn target_tiles(targets: &Targets, map: &Map, positions: &ReadStorage<Position>) -> Vec<usize> {
+    let entity_tile = |entity: &Entity| {
+        positions
+            .get(*entity)
+            .map(|pos| map.xy_idxi32(pos.x, pos.y))
+    };
+    match targets {
+        Targets::Single { target } => entity_tile(target).into_iter().collect(),
+        Targets::TargetList { targets } => targets.iter().filter_map(entity_tile).collect(),
+        Targets::Tiles { tiles } => tiles.clone(),
+    }
+}
+
+fn target_entities(targets: &Targets, map: &Map) -> Vec<Entity> {
+    match targets {
+        Targets::Single { target } => vec![*target],
+        Targets::TargetList { targets } => targets.clone(),
+        Targets::Tiles { tiles } => tiles
+            .iter()
+            .flat_map(|idx| map.tile_content[*idx].iter().copied())
+            .collect(),
+    }
+}
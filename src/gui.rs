@@ -3,11 +3,13 @@ use std::cmp::max;
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::{Entity, Join, World, WorldExt};
 
+use crate::camera;
 use crate::helpers::points_in_circle;
+use crate::memory_system::is_visible;
 
 use super::{
-    CombatStats, Equipped, InBackpack, KnownSpells, MagicStats, Map, Player, Position, State,
-    Viewshed,
+    wizard::WizardMode, CombatStats, Equipped, InBackpack, KnownSpells, LastSeen, MagicStats, Map,
+    Player, Position, State, Viewshed,
 };
 use super::{GameLog, Name, B_GUI_SIZE, HEIGHT, R_GUI_SIZE, WIDTH, WINDOW_WIDTH};
 
@@ -137,6 +139,22 @@ fn draw_player_stats(ecs: &World, ctx: &mut Rltk) {
             format!("depth:{}", map.depth),
         );
         y += y_spacing;
+        let wizard = ecs.fetch::<WizardMode>();
+        if wizard.enabled {
+            let label = if wizard.god_mode {
+                "WIZARD (god)"
+            } else {
+                "WIZARD"
+            };
+            ctx.print_color(
+                WIDTH + x_start,
+                y,
+                RGB::named(rltk::CYAN),
+                RGB::named(rltk::BLACK),
+                label,
+            );
+            y += y_spacing;
+        }
         ctx.print_color(
             WIDTH + x_start,
             y,
@@ -363,16 +381,36 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let positions = ecs.read_storage::<Position>();
 
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
-        return;
-    }
+    let mouse_world = match camera::mouse_world_pos(ecs, ctx) {
+        Some(pos) => pos,
+        None => return,
+    };
     let mut tooltip: Vec<String> = Vec::new();
     for (name, position) in (&names, &positions).join() {
         let idx = map.xy_idxi32(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
+        if position.x == mouse_world.x && position.y == mouse_world.y && map.visible_tiles[idx] {
             tooltip.push(name.name.to_string());
         }
     }
+    let last_seen = ecs.read_storage::<LastSeen>();
+    let entities = ecs.entities();
+    for (entity, name, memory) in (&entities, &names, &last_seen).join() {
+        if memory.depth == map.depth
+            && memory.x == mouse_world.x
+            && memory.y == mouse_world.y
+            && !is_visible(&map, positions.get(entity))
+        {
+            let turns = if memory.turns_ago == 1 {
+                "turn"
+            } else {
+                "turns"
+            };
+            tooltip.push(format!(
+                "{} (last seen {} {} ago)",
+                name.name, memory.turns_ago, turns
+            ));
+        }
+    }
 
     if !tooltip.is_empty() {
         let mut width: i32 = 0;
@@ -476,7 +514,8 @@ pub fn ranged_target(
         for idx in visible.visible_tiles.iter() {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
+                let screen = camera::world_to_screen(&gs.ecs, *idx);
+                ctx.set_bg(screen.x, screen.y, RGB::named(rltk::BLUE));
                 available_cells.push(idx);
             }
         }
@@ -486,26 +525,25 @@ pub fn ranged_target(
 
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
+    let mouse_world = camera::mouse_world_pos(&gs.ecs, ctx);
     let mut valid_target = false;
 
     let mut aoe_targets: Vec<Point> = Vec::new();
     for idx in available_cells.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
+        if Some(**idx) == mouse_world {
             aoe_targets = points_in_circle(**idx, radius);
             valid_target = true;
         }
     }
     for idx in aoe_targets.iter() {
-        ctx.set_bg(idx.x, idx.y, RGB::named(rltk::DARK_BLUE));
+        let screen = camera::world_to_screen(&gs.ecs, *idx);
+        ctx.set_bg(screen.x, screen.y, RGB::named(rltk::DARK_BLUE));
     }
 
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            return (ItemMenuResult::Selected, mouse_world);
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
@@ -604,13 +642,156 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
     }
 }
 
+pub fn wizard_menu(
+    ctx: &mut Rltk,
+    title: &str,
+    options: &[&str],
+) -> (ItemMenuResult, Option<usize>) {
+    let count = options.len();
+
+    let top = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        top - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        title,
+    );
+    ctx.print_color(
+        18,
+        top + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+
+    for (j, option) in options.iter().enumerate() {
+        let y = top + j as i32;
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+
+        ctx.print(21, y, option);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (ItemMenuResult::Selected, Some(selection as usize));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+pub fn wizard_pick_tile(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    prompt: &str,
+) -> (ItemMenuResult, Option<Point>) {
+    ctx.print_color(
+        5,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        prompt,
+    );
+
+    if ctx.key == Some(VirtualKeyCode::Escape) {
+        return (ItemMenuResult::Cancel, None);
+    }
+
+    let target = match camera::mouse_world_pos(&gs.ecs, ctx) {
+        Some(pos) => pos,
+        None => return (ItemMenuResult::NoResponse, None),
+    };
+
+    let mouse_pos = ctx.mouse_pos();
+    ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
+    if ctx.left_click {
+        return (ItemMenuResult::Selected, Some(target));
+    }
+    (ItemMenuResult::NoResponse, None)
+}
+
+/// Up and down pick the depth, enter confirms it. The returned depth is always the current pick.
+pub fn wizard_depth_menu(ctx: &mut Rltk, depth: i32) -> (ItemMenuResult, i32) {
+    ctx.draw_box(
+        15,
+        22,
+        31,
+        4,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        22,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Jump To Which Depth?",
+    );
+    ctx.print(21, 24, format!("< depth {} >", depth));
+    ctx.print_color(
+        18,
+        26,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, depth),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, depth),
+            VirtualKeyCode::Up | VirtualKeyCode::Right => (ItemMenuResult::NoResponse, depth + 1),
+            VirtualKeyCode::Down | VirtualKeyCode::Left => {
+                (ItemMenuResult::NoResponse, max(1, depth - 1))
+            }
+            VirtualKeyCode::Return => (ItemMenuResult::Selected, depth),
+            _ => (ItemMenuResult::NoResponse, depth),
+        },
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult {
     NoSelection,
     QuitToMenu,
 }
 
-pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
+pub fn game_over(ecs: &World, ctx: &mut Rltk) -> GameOverResult {
     ctx.print_color_centered(
         15,
         RGB::named(rltk::YELLOW),
@@ -629,6 +810,14 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
         RGB::named(rltk::BLACK),
         "That day, sadly, is not in this chapter..",
     );
+    if ecs.fetch::<WizardMode>().used {
+        ctx.print_color_centered(
+            19,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            "Wizard mode was used, this run is not scored.",
+        );
+    }
 
     ctx.print_color_centered(
         20,
@@ -647,6 +836,7 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
 pub enum MainMenuSelection {
     NewGame,
     LoadGame,
+    Difficulty,
     Quit,
 }
 
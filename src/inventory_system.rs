@@ -1,10 +1,13 @@
 use specs::prelude::*;
 
 use super::{
-    gamelog::GameLog, helpers::points_in_circle, particle_system::ParticleBuilder, AreaOfEffect,
-    CombatStats, Confusion, Consumable, DestroysWalls, Equippable, Equipped, InBackpack,
-    InflictsDamage, MagicStats, Map, Name, Position, ProvidesHealing, Spell, SufferDamage,
-    TileType, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    effects::{EffectQueue, EffectType, Targets},
+    gamelog::GameLog,
+    helpers::points_in_circle,
+    AreaOfEffect, CombatStats, Confusion, Consumable, DestroysWalls, DetectsItems, DetectsMonsters,
+    Equippable, Equipped, InBackpack, InflictsDamage, MagicMapper, MagicStats, Map, Name, Position,
+    ProvidesHealing, ProvidesMana, Spell, TileType, WantsToDropItem, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem,
 };
 
 pub struct ItemCollectionSystem {}
@@ -54,25 +57,27 @@ impl<'a> System<'a> for ItemUseSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, Consumable>,
         ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, ProvidesMana>,
         ReadStorage<'a, InflictsDamage>,
-        WriteStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, CombatStats>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, Confusion>,
         ReadStorage<'a, DestroysWalls>,
+        ReadStorage<'a, MagicMapper>,
+        ReadStorage<'a, DetectsMonsters>,
+        ReadStorage<'a, DetectsItems>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
         ReadStorage<'a, Spell>,
         WriteStorage<'a, MagicStats>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
+        WriteExpect<'a, EffectQueue>,
     );
 
     #[allow(clippy::cognitive_complexity)]
@@ -80,25 +85,27 @@ impl<'a> System<'a> for ItemUseSystem {
         let (
             player_entity,
             mut gamelog,
-            mut map,
+            map,
             entities,
             mut wants_use,
             names,
             consumables,
             healing,
+            mana_restoring,
             inflict_damage,
-            mut combat_stats,
-            mut suffer_damage,
+            combat_stats,
             aoe,
-            mut confused,
+            confused,
             destroys_walls,
+            magic_mappers,
+            detects_monsters,
+            detects_items,
             equippable,
             mut equipped,
             mut backpack,
             spells,
             mut magic_stats,
-            mut particle_builder,
-            positions,
+            mut effects,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
@@ -123,20 +130,7 @@ impl<'a> System<'a> for ItemUseSystem {
                         }
                         Some(area_effect) => {
                             // AoE
-                            {
-                                let target_points = points_in_circle(target, area_effect.radius);
-                                for point in target_points.iter() {
-                                    blast_tiles.push(*point);
-                                    particle_builder.request(
-                                        point.x,
-                                        point.y,
-                                        rltk::RGB::named(rltk::ORANGE),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('░'),
-                                        200.0,
-                                    );
-                                }
-                            }
+                            blast_tiles = points_in_circle(target, area_effect.radius);
                             //rltk::field_of_view(target, area_effect.radius, &*map);
                             blast_tiles.retain(|p| {
                                 p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
@@ -147,31 +141,50 @@ impl<'a> System<'a> for ItemUseSystem {
                                     targets.push(*mob);
                                 }
                             }
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Particle {
+                                    glyph: rltk::to_cp437('░'),
+                                    fg: rltk::RGB::named(rltk::ORANGE),
+                                    bg: rltk::RGB::named(rltk::BLACK),
+                                    lifetime: 200.0,
+                                },
+                                Targets::Tiles {
+                                    tiles: blast_tiles
+                                        .iter()
+                                        .map(|p| map.xy_idxi32(p.x, p.y))
+                                        .collect(),
+                                },
+                            );
                         }
                     }
                 }
             }
 
-            let destroys_wall = destroys_walls.get(useitem.item);
-            match destroys_wall {
-                None => {}
-                Some(_) => {
-                    for destruction_point in blast_tiles.iter() {
-                        let idx = map.xy_idxi32(destruction_point.x, destruction_point.y);
-                        match map.tiles[idx] {
-                            TileType::Wall => {
-                                map.tiles[idx] = TileType::Floor;
-                                used_item = true;
-                            }
-                            TileType::Floor => {}
-                            TileType::DownStairs => {}
-                        }
-                    }
+            if let Some(destroys_walls) = destroys_walls.get(useitem.item) {
+                let hits_wall = blast_tiles
+                    .iter()
+                    .any(|p| map.tiles[map.xy_idxi32(p.x, p.y)] == TileType::Wall);
+                if hits_wall {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::DamageTerrain {
+                            amount: destroys_walls.damage,
+                        },
+                        Targets::Tiles {
+                            tiles: blast_tiles
+                                .iter()
+                                .map(|p| map.xy_idxi32(p.x, p.y))
+                                .collect(),
+                        },
+                    );
+                    used_item = true;
                 }
             }
+
             // if it is spell reduce mana
             let spell = spells.get(useitem.item);
-            for (player, mut magic_stats) in (&entities, &mut magic_stats).join() {
+            for (_player, magic_stats) in (&entities, &mut magic_stats).join() {
                 match spell {
                     None => {}
                     Some(spell) => {
@@ -190,9 +203,24 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => {}
                 Some(healer) => {
                     for target in targets.iter() {
-                        let stats = combat_stats.get_mut(*target);
-                        if let Some(stats) = stats {
-                            stats.hp = i32::min(stats.max_hp, stats.hp + healer.healing_amount);
+                        if combat_stats.get(*target).is_some() {
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Healing {
+                                    amount: healer.healing_amount,
+                                },
+                                Targets::Single { target: *target },
+                            );
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Particle {
+                                    glyph: rltk::to_cp437('♥'),
+                                    fg: rltk::RGB::named(rltk::GREEN),
+                                    bg: rltk::RGB::named(rltk::BLACK),
+                                    lifetime: 200.0,
+                                },
+                                Targets::Single { target: *target },
+                            );
                             if entity == *player_entity {
                                 gamelog.entries.push(format!(
                                     "You use the {}, healing {} hp.",
@@ -201,30 +229,123 @@ impl<'a> System<'a> for ItemUseSystem {
                                 ));
                             }
                             used_item = true;
+                        }
+                    }
+                }
+            }
 
-                            let pos = positions.get(*target);
-                            if let Some(pos) = pos {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    rltk::RGB::named(rltk::GREEN),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('♥'),
-                                    200.0,
-                                );
-                            }
+            // If it restores mana, top up the target's mana
+            if let Some(restorer) = mana_restoring.get(useitem.item) {
+                for target in targets.iter() {
+                    if magic_stats.get(*target).is_some() {
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::RestoreMana {
+                                amount: restorer.mana_amount,
+                            },
+                            Targets::Single { target: *target },
+                        );
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('*'),
+                                fg: rltk::RGB::named(rltk::BLUE),
+                                bg: rltk::RGB::named(rltk::BLACK),
+                                lifetime: 200.0,
+                            },
+                            Targets::Single { target: *target },
+                        );
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You use the {}, restoring {} mana.",
+                                names.get(useitem.item).unwrap().name,
+                                restorer.mana_amount
+                            ));
                         }
+                        used_item = true;
                     }
                 }
             }
 
+            // Divination works on whoever used the item, wherever they aimed it
+            if let Some(mapper) = magic_mappers.get(useitem.item) {
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::RevealMap {
+                        radius: mapper.radius,
+                    },
+                    Targets::Single { target: entity },
+                );
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You use the {}, and the layout of the level comes to mind.",
+                        names.get(useitem.item).unwrap().name
+                    ));
+                }
+                used_item = true;
+            }
+
+            if let Some(detector) = detects_monsters.get(useitem.item) {
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::DetectMonsters {
+                        turns: detector.duration,
+                    },
+                    Targets::Single { target: entity },
+                );
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You use the {}, and sense the minds around you.",
+                        names.get(useitem.item).unwrap().name
+                    ));
+                }
+                used_item = true;
+            }
+
+            if let Some(detector) = detects_items.get(useitem.item) {
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::DetectItems {
+                        turns: detector.duration,
+                    },
+                    Targets::Single { target: entity },
+                );
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You use the {}, and sense the objects lying around you.",
+                        names.get(useitem.item).unwrap().name
+                    ));
+                }
+                used_item = true;
+            }
+
             // If it inflicts damage, apply it to the target cell
             let item_damages = inflict_damage.get(useitem.item);
             match item_damages {
                 None => {}
                 Some(damage) => {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Damage {
+                            amount: damage.damage,
+                        },
+                        Targets::TargetList {
+                            targets: targets.clone(),
+                        },
+                    );
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Particle {
+                            glyph: rltk::to_cp437('‼'),
+                            fg: rltk::RGB::named(rltk::RED),
+                            bg: rltk::RGB::named(rltk::BLACK),
+                            lifetime: 200.0,
+                        },
+                        Targets::TargetList {
+                            targets: targets.clone(),
+                        },
+                    );
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
                         if entity == *player_entity {
                             let mob_name = names.get(*mob).unwrap();
                             let item_name = names.get(useitem.item).unwrap();
@@ -235,49 +356,35 @@ impl<'a> System<'a> for ItemUseSystem {
                         }
 
                         used_item = true;
-
-                        let pos = positions.get(*mob);
-                        if let Some(pos) = pos {
-                            particle_builder.request(
-                                pos.x,
-                                pos.y,
-                                rltk::RGB::named(rltk::RED),
-                                rltk::RGB::named(rltk::BLACK),
-                                rltk::to_cp437('‼'),
-                                200.0,
-                            );
-                        }
                     }
                 }
             }
 
-            // Can it pass along confusion? Note the use of scopes to escape from the borrow checker!
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(useitem.item);
-                match causes_confusion {
-                    None => {}
-                    Some(confusion) => {
-                        used_item = true;
-                        for mob in targets.iter() {
-                            add_confusion.push((*mob, confusion.duration));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
-                                ));
-                            }
+            // Can it pass along confusion?
+            let causes_confusion = confused.get(useitem.item);
+            match causes_confusion {
+                None => {}
+                Some(confusion) => {
+                    used_item = true;
+                    for mob in targets.iter() {
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Confusion {
+                                turns: confusion.duration,
+                            },
+                            Targets::Single { target: *mob },
+                        );
+                        if entity == *player_entity {
+                            let mob_name = names.get(*mob).unwrap();
+                            let item_name = names.get(useitem.item).unwrap();
+                            gamelog.entries.push(format!(
+                                "You use {} on {}, confusing them.",
+                                item_name.name, mob_name.name
+                            ));
                         }
                     }
                 }
             }
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { duration: mob.1 })
-                    .expect("Unable to insert status");
-            }
 
             let item_equippable = equippable.get(useitem.item);
             match item_equippable {
@@ -0,0 +1,696 @@
+extern crate rltk;
+extern crate serde;
+extern crate specs;
+
+pub use components::*;
+use gamelog::GameLog;
+use gui::MainMenuSelection;
+pub use map::*;
+use player::*;
+pub use rect::Rect;
+use rltk::{GameState, Point, Rltk};
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
+pub use visibility_system::*;
+
+pub mod camera;
+pub mod components;
+pub mod console;
+pub mod damage_system;
+pub mod detection_system;
+pub mod difficulty;
+pub mod dispatchers;
+pub mod dungeon;
+pub mod effects;
+pub mod gamelog;
+pub mod gui;
+pub mod helpers;
+pub mod inventory_system;
+pub mod lighting_system;
+pub mod map;
+pub mod map_builders;
+pub mod map_indexing_system;
+pub mod memory_system;
+pub mod melee_combat_system;
+pub mod menu;
+pub mod monster_ai_systems;
+pub mod particle_system;
+pub mod player;
+pub mod random_table;
+pub mod rect;
+pub mod saveload_system;
+pub mod spawner;
+pub mod spells;
+pub mod themes;
+pub mod visibility_system;
+pub mod wizard;
+
+pub const WINDOW_WIDTH: usize = 80;
+pub const WINDOW_HEIGHT: usize = 50;
+pub const R_GUI_SIZE: usize = 18;
+pub const B_GUI_SIZE: usize = 7;
+pub const WIDTH: usize = WINDOW_WIDTH - R_GUI_SIZE;
+pub const HEIGHT: usize = WINDOW_HEIGHT - B_GUI_SIZE;
+
+/// Debug flag: replay the generation of every new level before play starts. The console's
+/// `mapgen` command toggles it at runtime.
+pub const SHOW_MAPGEN_VISUALIZER: bool = false;
+/// How long each map generation snapshot stays on screen by default, in milliseconds.
+pub const MAPGEN_FRAME_TIME: f32 = 100.0;
+
+/// Settings for replaying map generation, see `RunState::MapGeneration`.
+pub struct MapGenVisualizer {
+    pub enabled: bool,
+    pub frame_time: f32,
+    /// Why levels were thrown away while building the current one, see `build_valid_map`.
+    pub rejections: Vec<String>,
+}
+
+pub struct State {
+    pub ecs: World,
+    pre_run: Dispatcher<'static, 'static>,
+    player_turn: Dispatcher<'static, 'static>,
+    monster_turn: Dispatcher<'static, 'static>,
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+    /// The difficulty the first level was last built for, so a new game can tell whether the
+    /// setting has changed on the main menu since.
+    first_level_difficulty: difficulty::Difficulty,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    PlayerTurn,
+    MonsterTurn,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting {
+        range: i32,
+        targetable: Entity,
+    },
+    NextLevel,
+    PreviousLevel,
+    ShowRemoveItem,
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    GameOver,
+    SaveGame,
+    WizardTeleport,
+    WizardSpawn,
+    WizardPlaceSpawn {
+        index: usize,
+    },
+    WizardGrantSpell,
+    WizardGotoDepth {
+        depth: i32,
+    },
+    /// Replays the snapshots taken while building the current level, then moves on to
+    /// `State::mapgen_next_state`. Passes straight through unless the visualizer is enabled.
+    MapGeneration,
+}
+
+impl State {
+    /// Creates the world with every component registered and every resource the game needs,
+    /// including a freshly generated first level, and leaves it at the main menu.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            pre_run: dispatchers::pre_run_dispatcher(),
+            player_turn: dispatchers::player_turn_dispatcher(),
+            monster_turn: dispatchers::monster_turn_dispatcher(),
+            mapgen_next_state: None,
+            mapgen_history: Vec::new(),
+            mapgen_index: 0,
+            mapgen_timer: 0.0,
+            first_level_difficulty: difficulty::Difficulty::default(),
+        };
+
+        register_components(&mut gs.ecs);
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+        gs.ecs.insert(Map::new(1, WIDTH as i32, HEIGHT as i32));
+        gs.ecs.insert(rltk::RandomNumberGenerator::new());
+        gs.ecs.insert(difficulty::Difficulty::default());
+        gs.ecs.insert(MapGenVisualizer {
+            enabled: SHOW_MAPGEN_VISUALIZER,
+            frame_time: MAPGEN_FRAME_TIME,
+            rejections: Vec::new(),
+        });
+        let player_start = gs.generate_world_map(1);
+        let (player_x, player_y) = (player_start.x, player_start.y);
+
+        let player_entity = spawner::player(&mut gs.ecs, player_x, player_y);
+        gs.ecs.insert(Point::new(player_x, player_y));
+        gs.ecs.insert(RunState::MainMenu {
+            menu_selection: MainMenuSelection::NewGame,
+        });
+        gs.ecs.insert(particle_system::ParticleBuilder::new());
+        gs.ecs.insert(effects::EffectQueue::new());
+        gs.ecs.insert(player_entity);
+        gs.ecs.insert(rltk::RandomNumberGenerator::new());
+        gs.ecs.insert(GameLog {
+            entries: vec!["Welcome to Spell Thief!".to_string()],
+        });
+        gs.ecs.insert(wizard::WizardMode::default());
+        gs.ecs.insert(dungeon::DungeonMaster::default());
+        gs.ecs.insert(console::Console::new());
+        gs
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut Rltk) {
+        ctx.cls();
+        particle_system::cull_dead_particles(&mut self.ecs, ctx);
+
+        if *self.ecs.fetch::<RunState>() != RunState::MapGeneration {
+            camera::render_camera(&self.ecs, ctx);
+            gui::draw_ui(&self.ecs, ctx);
+        }
+
+        if console::console_input(&mut self.ecs, ctx) {
+            // The console swallows all input while it is open
+            ctx.key = None;
+            ctx.left_click = false;
+        }
+
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        match newrunstate {
+            RunState::PreRun => {
+                self.pre_run.dispatch(&self.ecs);
+                self.ecs.maintain();
+                newrunstate = RunState::AwaitingInput;
+            }
+            RunState::AwaitingInput => {
+                newrunstate = player_input(self, ctx);
+            }
+            RunState::PlayerTurn => {
+                self.player_turn.dispatch(&self.ecs);
+                self.ecs.maintain();
+                newrunstate = RunState::MonsterTurn;
+            }
+            RunState::MonsterTurn => {
+                self.monster_turn.dispatch(&self.ecs);
+                self.ecs.maintain();
+                newrunstate = RunState::AwaitingInput;
+            }
+
+            RunState::NextLevel => {
+                let current_depth = self.ecs.fetch::<Map>().depth;
+                self.goto_level(current_depth + 1);
+                newrunstate = self.replay_map_generation(RunState::PreRun);
+            }
+            RunState::PreviousLevel => {
+                let current_depth = self.ecs.fetch::<Map>().depth;
+                self.goto_level(current_depth - 1);
+                newrunstate = RunState::PreRun;
+            }
+            RunState::ShowInventory => {
+                let result = gui::show_inventory(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item_entity);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting {
+                                range: is_item_ranged.range,
+                                targetable: item_entity,
+                            };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item: item_entity,
+                                        target: None,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = gui::drop_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToDropItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowTargeting {
+                range,
+                targetable: item,
+            } => {
+                let mut radius = 1;
+
+                {
+                    let aoe = self.ecs.read_storage::<AreaOfEffect>();
+                    let is_aoe = aoe.get(item);
+                    if let Some(is_aoe) = is_aoe {
+                        radius = is_aoe.radius;
+                    }
+                }
+                let result = gui::ranged_target(self, ctx, range, radius);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToUseItem {
+                                    item,
+                                    target: result.1,
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let result = gui::remove_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToRemoveItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::SaveGame => {
+                saveload_system::save_game(&mut self.ecs);
+                newrunstate = RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::LoadGame,
+                };
+            }
+            RunState::GameOver => {
+                let result = gui::game_over(&self.ecs, ctx);
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        };
+                    }
+                }
+            }
+            RunState::WizardTeleport => {
+                let result = gui::wizard_pick_tile(self, ctx, "Teleport where?");
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        if wizard::teleport_player(&mut self.ecs, result.1.unwrap()) {
+                            newrunstate = RunState::PreRun;
+                        }
+                    }
+                }
+            }
+            RunState::WizardSpawn => {
+                let result = gui::wizard_menu(ctx, "Spawn What?", &spawner::SPAWNABLE_NAMES);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = RunState::WizardPlaceSpawn {
+                            index: result.1.unwrap(),
+                        };
+                    }
+                }
+            }
+            RunState::WizardPlaceSpawn { index } => {
+                let name = spawner::SPAWNABLE_NAMES[index];
+                let result = gui::wizard_pick_tile(self, ctx, &format!("Place {} where?", name));
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        if wizard::spawn_at(&mut self.ecs, name, result.1.unwrap()).is_some() {
+                            let mut gamelog = self.ecs.fetch_mut::<GameLog>();
+                            gamelog.entries.push(format!("You conjure a {}.", name));
+                        }
+                        newrunstate = RunState::PreRun;
+                    }
+                }
+            }
+            RunState::WizardGrantSpell => {
+                let result = gui::wizard_menu(ctx, "Learn Which Spell?", &spells::SPELL_NAMES);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let name = spells::SPELL_NAMES[result.1.unwrap()];
+                        let message = match wizard::grant_spell(&mut self.ecs, name) {
+                            wizard::GrantSpellResult::Learned => format!("You learn {}.", name),
+                            wizard::GrantSpellResult::AlreadyKnown => {
+                                format!("You already know {}.", name)
+                            }
+                            wizard::GrantSpellResult::NoSuchSpell => {
+                                format!("There is no spell called {}.", name)
+                            }
+                        };
+                        self.ecs.fetch_mut::<GameLog>().entries.push(message);
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::WizardGotoDepth { depth } => {
+                let result = gui::wizard_depth_menu(ctx, depth);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {
+                        newrunstate = RunState::WizardGotoDepth { depth: result.1 }
+                    }
+                    gui::ItemMenuResult::Selected => {
+                        if result.1 == self.ecs.fetch::<Map>().depth {
+                            newrunstate = RunState::AwaitingInput;
+                        } else {
+                            self.goto_level(result.1);
+                            newrunstate = self.replay_map_generation(RunState::PreRun);
+                        }
+                    }
+                }
+            }
+            RunState::MapGeneration => {
+                let (enabled, frame_time) = {
+                    let visualizer = self.ecs.fetch::<MapGenVisualizer>();
+                    (visualizer.enabled, visualizer.frame_time)
+                };
+                if ctx.key.is_some() {
+                    // Any key skips the rest of the replay
+                    self.mapgen_index = self.mapgen_history.len();
+                }
+                if !enabled || self.mapgen_index >= self.mapgen_history.len() {
+                    self.mapgen_history.clear();
+                    newrunstate = self.mapgen_next_state.take().unwrap_or(RunState::PreRun);
+                } else {
+                    camera::render_snapshot(&self.mapgen_history[self.mapgen_index], ctx);
+                    self.mapgen_timer += ctx.frame_time_ms;
+                    if self.mapgen_timer > frame_time {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                    }
+                }
+            }
+            RunState::MainMenu { .. } => {
+                let result = menu::main_menu(self, ctx);
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::MainMenuResult::Selected { selected } => match selected {
+                        gui::MainMenuSelection::NewGame => {
+                            // The first level was built before the menu came up, so it needs
+                            // rebuilding if the difficulty was changed there
+                            let difficulty = *self.ecs.fetch::<difficulty::Difficulty>();
+                            if difficulty != self.first_level_difficulty {
+                                self.game_over_cleanup();
+                            }
+                            newrunstate = self.replay_map_generation(RunState::PreRun)
+                        }
+                        gui::MainMenuSelection::Difficulty => {
+                            let next = self.ecs.fetch::<difficulty::Difficulty>().next();
+                            *self.ecs.write_resource::<difficulty::Difficulty>() = next;
+                            newrunstate = RunState::MainMenu {
+                                menu_selection: gui::MainMenuSelection::Difficulty,
+                            }
+                        }
+                        gui::MainMenuSelection::LoadGame => {
+                            saveload_system::load_game(&mut self.ecs);
+                            newrunstate = RunState::AwaitingInput;
+                            saveload_system::delete_save();
+                        }
+                        gui::MainMenuSelection::Quit => {
+                            ::std::process::exit(0);
+                        }
+                    },
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        console::draw_console(&self.ecs, ctx);
+        damage_system::delete_the_dead(&mut self.ecs);
+    }
+}
+
+impl State {
+    fn game_over_cleanup(&mut self) {
+        // Delete everything
+        let mut to_delete = Vec::new();
+        for e in self.ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            self.ecs.delete_entity(*del).expect("Deletion failed");
+        }
+
+        *self.ecs.write_resource::<wizard::WizardMode>() = wizard::WizardMode::default();
+        *self.ecs.write_resource::<dungeon::DungeonMaster>() = dungeon::DungeonMaster::default();
+
+        // Build a new map, spawn bad guys and place the player
+        let player_start = self.generate_world_map(1);
+
+        // Place the player and update resources
+        let (player_x, player_y) = (player_start.x, player_start.y);
+        let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
+        let mut player_position = self.ecs.write_resource::<Point>();
+        *player_position = Point::new(player_x, player_y);
+        let mut position_components = self.ecs.write_storage::<Position>();
+        let mut player_entity_writer = self.ecs.write_resource::<Entity>();
+        *player_entity_writer = player_entity;
+        let player_pos_comp = position_components.get_mut(player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = player_x;
+            player_pos_comp.y = player_y;
+        }
+
+        // Mark the player's visibility as dirty
+        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
+        let vs = viewshed_components.get_mut(player_entity);
+        if let Some(vs) = vs {
+            vs.dirty = true;
+        }
+    }
+
+    /// Builds a level of the given depth, makes it the current map and spawns its entities.
+    /// Returns where the player should start.
+    fn generate_world_map(&mut self, new_depth: i32) -> Position {
+        let record_history = self.ecs.fetch::<MapGenVisualizer>().enabled;
+        let difficulty = *self.ecs.fetch::<difficulty::Difficulty>();
+        if new_depth == 1 {
+            self.first_level_difficulty = difficulty;
+        }
+        let mut builder = {
+            let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+            map_builders::build_valid_map(
+                new_depth,
+                WIDTH as i32,
+                HEIGHT as i32,
+                difficulty,
+                &mut rng,
+                record_history,
+            )
+        };
+        self.mapgen_history = std::mem::take(&mut builder.build_data.history.snapshots);
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.ecs.write_resource::<MapGenVisualizer>().rejections =
+            std::mem::take(&mut builder.rejections);
+        *self.ecs.write_resource::<Map>() = builder.build_data.map.clone();
+        builder.spawn_entities(&mut self.ecs);
+        builder
+            .build_data
+            .starting_position
+            .expect("Map builder chain did not pick a starting position")
+    }
+
+    /// Shows how the level just generated was built, if the visualizer is on, before going on
+    /// to `next_state`.
+    fn replay_map_generation(&mut self, next_state: RunState) -> RunState {
+        self.mapgen_next_state = Some(next_state);
+        RunState::MapGeneration
+    }
+
+    /// Leaves the current level for `new_depth`, handing it to the dungeon master. A level
+    /// visited before is restored as it was left, with the player on the stairs leading back
+    /// where they came from. Otherwise a new one is generated. Does nothing if `new_depth` is
+    /// the current depth.
+    fn goto_level(&mut self, new_depth: i32) {
+        let old_depth = self.ecs.fetch::<Map>().depth;
+        if new_depth == old_depth {
+            return;
+        }
+        dungeon::freeze_level_entities(&mut self.ecs);
+        {
+            let map = self.ecs.fetch::<Map>();
+            self.ecs
+                .write_resource::<dungeon::DungeonMaster>()
+                .store_map(&map);
+        }
+
+        let stored_map = self
+            .ecs
+            .fetch::<dungeon::DungeonMaster>()
+            .get_map(new_depth);
+        let first_visit = stored_map.is_none();
+        let player_start = match stored_map {
+            Some(mut map) => {
+                map.tile_content = vec![Vec::new(); map.tile_count()];
+                let arrival = if new_depth > old_depth {
+                    TileType::UpStairs
+                } else {
+                    TileType::DownStairs
+                };
+                let arrival_idx = map
+                    .tiles
+                    .iter()
+                    .position(|tile| *tile == arrival)
+                    .or_else(|| map.tiles.iter().position(|tile| tile_walkable(*tile)))
+                    .expect("Stored level has no floor to arrive on");
+                let (x, y) = map.idx_to_xy(arrival_idx);
+                *self.ecs.write_resource::<Map>() = map;
+                dungeon::thaw_level_entities(&mut self.ecs);
+                Position { x, y }
+            }
+            None => self.generate_world_map(new_depth),
+        };
+
+        // Place the player and update resources
+        let (player_x, player_y) = (player_start.x, player_start.y);
+        let mut player_position = self.ecs.write_resource::<Point>();
+        *player_position = Point::new(player_x, player_y);
+        let mut position_components = self.ecs.write_storage::<Position>();
+        let player_entity = self.ecs.fetch::<Entity>();
+        let player_pos_comp = position_components.get_mut(*player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = player_x;
+            player_pos_comp.y = player_y;
+        }
+
+        // Mark the player's visibility as dirty
+        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
+        let vs = viewshed_components.get_mut(*player_entity);
+        if let Some(vs) = vs {
+            vs.dirty = true;
+        }
+
+        // Notify the player, and the first time they reach a level give them some health and a
+        // taste of what it's like
+        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+        let going_up = new_depth < old_depth;
+        if !first_visit {
+            let message = if going_up {
+                format!("You climb back up to depth {}.", new_depth)
+            } else {
+                format!("You descend back to depth {}.", new_depth)
+            };
+            gamelog.entries.push(message);
+        } else {
+            let message = if going_up {
+                format!("You climb up to depth {}, and take a moment to heal.", new_depth)
+            } else {
+                "You descend to the next level, and take a moment to heal.".to_string()
+            };
+            gamelog.entries.push(message);
+            let theme = self.ecs.fetch::<Map>().theme;
+            gamelog.entries.push(theme.flavour().to_string());
+            let mut player_health_store = self.ecs.write_storage::<CombatStats>();
+            let player_health = player_health_store.get_mut(*player_entity);
+            if let Some(player_health) = player_health {
+                player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+            }
+        }
+    }
+}
+
+pub fn register_components(ecs: &mut World) {
+    ecs.register::<Position>();
+    ecs.register::<OtherLevelPosition>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Monster>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<CombatStats>();
+    ecs.register::<MagicStats>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<Item>();
+    ecs.register::<Potion>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<Item>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<Consumable>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<ProvidesMana>();
+    ecs.register::<Ranged>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<Confusion>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<CanTargetAnything>();
+    ecs.register::<DestroysWalls>();
+    ecs.register::<SlowedByTerrain>();
+    ecs.register::<LightSource>();
+    ecs.register::<MagicMapper>();
+    ecs.register::<DetectsMonsters>();
+    ecs.register::<DetectsItems>();
+    ecs.register::<SeesMonsters>();
+    ecs.register::<SeesItems>();
+    ecs.register::<LastSeen>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleePowerBonus>();
+    ecs.register::<DefenseBonus>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+    ecs.register::<KnownSpells>();
+    ecs.register::<Spell>();
+    ecs.register::<ParticleLifetime>();
+}
@@ -0,0 +1,98 @@
+use rltk::{field_of_view, Point, RGB};
+use specs::prelude::*;
+
+use super::{Equipped, LightSource, Map, Position, Viewshed};
+
+/// Ambient light on the first level; every level below loses `AMBIENT_FALLOFF` of it.
+const SURFACE_AMBIENT: f32 = 1.0;
+const AMBIENT_FALLOFF: f32 = 0.15;
+const MIN_AMBIENT: f32 = 0.05;
+/// Tiles dimmer than this can't be seen, unless they are right next to the player.
+const LIGHT_THRESHOLD: f32 = 0.15;
+/// The least a visible tile is drawn with, so that dim tiles stay readable.
+const MIN_VISIBLE_LIGHT: f32 = 0.35;
+
+/// How bright a level is away from any light source.
+pub fn ambient_light(depth: i32) -> f32 {
+    f32::max(
+        MIN_AMBIENT,
+        SURFACE_AMBIENT - (depth - 1) as f32 * AMBIENT_FALLOFF,
+    )
+}
+
+/// Whether the tile has enough light on it to be seen.
+pub fn is_lit(map: &Map, idx: usize) -> bool {
+    let light = map.light[idx];
+    f32::max(light.r, f32::max(light.g, light.b)) >= LIGHT_THRESHOLD
+}
+
+/// The colour a visible tile's glyph is multiplied by.
+pub fn light_tint(map: &Map, idx: usize) -> RGB {
+    let light = map.light[idx];
+    RGB::from_f32(
+        f32::max(MIN_VISIBLE_LIGHT, light.r),
+        f32::max(MIN_VISIBLE_LIGHT, light.g),
+        f32::max(MIN_VISIBLE_LIGHT, light.b),
+    )
+}
+
+/// Rebuilds the map's light from the level's ambient light and every light source: lights
+/// standing on the map, and lights equipped by something that is. Marks the player's view
+/// dirty when the light changes, since it decides what they can see.
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, LightSource>,
+        ReadStorage<'a, Equipped>,
+        WriteStorage<'a, Viewshed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, player_entity, positions, light_sources, equipped, mut viewsheds) = data;
+
+        let ambient = ambient_light(map.depth);
+        let mut light = vec![RGB::from_f32(ambient, ambient, ambient); map.tile_count()];
+
+        let mut sources: Vec<(Point, &LightSource)> = Vec::new();
+        for (pos, source) in (&positions, &light_sources).join() {
+            sources.push((Point::new(pos.x, pos.y), source));
+        }
+        for (source, equipped) in (&light_sources, &equipped).join() {
+            if let Some(pos) = positions.get(equipped.owner) {
+                sources.push((Point::new(pos.x, pos.y), source));
+            }
+        }
+
+        for (origin, source) in sources.iter() {
+            for tile in field_of_view(*origin, source.range, &*map).iter() {
+                if tile.x < 0 || tile.x >= map.width || tile.y < 0 || tile.y >= map.height {
+                    continue;
+                }
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(*origin, *tile);
+                let intensity = 1.0 - distance / (source.range as f32 + 1.0);
+                if intensity <= 0.0 {
+                    continue;
+                }
+                let idx = map.xy_idxi32(tile.x, tile.y);
+                let lit = light[idx] + source.color * intensity;
+                light[idx] = RGB::from_f32(
+                    f32::min(1.0, lit.r),
+                    f32::min(1.0, lit.g),
+                    f32::min(1.0, lit.b),
+                );
+            }
+        }
+
+        if light != map.light {
+            map.light = light;
+            if let Some(viewshed) = viewsheds.get_mut(*player_entity) {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
@@ -1,20 +1,76 @@
 use std::cmp::{max, min};
 
-use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator, Rltk, RGB};
+use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator, RGB};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-use {crate::HEIGHT, crate::WIDTH};
-
+use super::lighting_system::light_tint;
+use super::themes::LevelTheme;
 use super::Rect;
-use super::MAP_COUNT;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
+    Water,
+    DeepWater,
+    Lava,
+    Grass,
+    Bridge,
+    Rubble,
+    Chasm,
+}
+
+/// What a wall is made of, which decides how much punishment it takes to bring it down.
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum WallMaterial {
+    Earth,
+    Stone,
+    /// Vault walls, which nothing can break.
+    Reinforced,
+}
+
+/// How much damage a wall of the given material takes before it collapses into rubble, or
+/// `None` if it can't be destroyed.
+pub fn wall_durability(material: WallMaterial) -> Option<i32> {
+    match material {
+        WallMaterial::Earth => Some(10),
+        WallMaterial::Stone => Some(30),
+        WallMaterial::Reinforced => None,
+    }
+}
+
+/// Whether anything can walk onto the tile. Deep water, lava and chasms are as impassable as
+/// walls, but can be seen across.
+pub fn tile_walkable(tile: TileType) -> bool {
+    match tile {
+        TileType::Floor
+        | TileType::DownStairs
+        | TileType::UpStairs
+        | TileType::Water
+        | TileType::Grass
+        | TileType::Bridge
+        | TileType::Rubble => true,
+        TileType::Wall | TileType::DeepWater | TileType::Lava | TileType::Chasm => false,
+    }
+}
+
+/// Whether the tile blocks line of sight.
+pub fn tile_opaque(tile: TileType) -> bool {
+    tile == TileType::Wall
+}
+
+/// How much stepping onto the tile costs when pathing, compared to open floor.
+pub fn tile_cost(tile: TileType) -> f32 {
+    match tile {
+        TileType::Water => 2.0,
+        TileType::Rubble => 1.5,
+        TileType::Grass => 1.1,
+        _ => 1.0,
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -28,6 +84,12 @@ pub struct Map {
     pub height: i32,
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
+    pub materials: Vec<WallMaterial>,
+    /// Damage taken so far by walls that haven't collapsed yet.
+    pub wall_damage: HashMap<usize, i32>,
+    /// Light falling on each tile, rebuilt every turn by the `LightingSystem`.
+    pub light: Vec<RGB>,
+    pub theme: LevelTheme,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -42,7 +104,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        tile_opaque(self.tiles[idx])
     }
 
     fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
@@ -53,30 +115,30 @@ impl BaseMap for Map {
 
         // Cardinal directions
         if self.is_exit_valid(x - 1, y) {
-            exits.push((idx - 1, 1.0))
+            exits.push((idx - 1, self.step_cost(idx - 1, 1.0)))
         };
         if self.is_exit_valid(x + 1, y) {
-            exits.push((idx + 1, 1.0))
+            exits.push((idx + 1, self.step_cost(idx + 1, 1.0)))
         };
         if self.is_exit_valid(x, y - 1) {
-            exits.push((idx - w, 1.0))
+            exits.push((idx - w, self.step_cost(idx - w, 1.0)))
         };
         if self.is_exit_valid(x, y + 1) {
-            exits.push((idx + w, 1.0))
+            exits.push((idx + w, self.step_cost(idx + w, 1.0)))
         };
 
         // Diagonals
         if self.is_exit_valid(x - 1, y - 1) {
-            exits.push(((idx - w) - 1, 1.45));
+            exits.push(((idx - w) - 1, self.step_cost((idx - w) - 1, 1.45)));
         }
         if self.is_exit_valid(x + 1, y - 1) {
-            exits.push(((idx - w) + 1, 1.45));
+            exits.push(((idx - w) + 1, self.step_cost((idx - w) + 1, 1.45)));
         }
         if self.is_exit_valid(x - 1, y + 1) {
-            exits.push(((idx + w) - 1, 1.45));
+            exits.push(((idx + w) - 1, self.step_cost((idx + w) - 1, 1.45)));
         }
         if self.is_exit_valid(x + 1, y + 1) {
-            exits.push(((idx + w) + 1, 1.45));
+            exits.push(((idx + w) + 1, self.step_cost((idx + w) + 1, 1.45)));
         }
 
         exits
@@ -90,46 +152,135 @@ impl BaseMap for Map {
     }
 }
 
-pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
-    let map = ecs.fetch::<Map>();
-
-    let mut x = 0;
-    let mut y = 0;
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        if map.revealed_tiles[idx] {
-            let glyph;
-            let mut fg;
-            let mut bg = RGB::from_f32(0., 0., 0.);
-            match tile {
-                TileType::Floor => {
-                    glyph = rltk::to_cp437('.');
-                    fg = RGB::from_f32(0.5, 0.5, 0.5);
-                }
-                TileType::Wall => {
-                    glyph = wall_glyph(&*map, x, y);
-                    fg = RGB::from_f32(0., 1.0, 0.);
-                }
-                TileType::DownStairs => {
-                    glyph = rltk::to_cp437('>');
-                    fg = RGB::from_f32(0., 1.0, 1.0);
+/// Glyph and colours for a revealed tile, greyed out when it is not currently visible.
+pub fn tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
+    let (x, y) = map.idx_to_xy(idx);
+    let glyph;
+    let mut fg;
+    let mut bg = RGB::from_f32(0., 0., 0.);
+    let palette = map.theme.palette();
+    match map.tiles[idx] {
+        TileType::Floor => {
+            glyph = rltk::to_cp437('.');
+            fg = palette.floor;
+        }
+        TileType::Wall => {
+            glyph = wall_glyph(map, x, y);
+            fg = match map.materials[idx] {
+                WallMaterial::Earth => palette.earth_wall,
+                WallMaterial::Stone => palette.stone_wall,
+                WallMaterial::Reinforced => RGB::from_f32(0.6, 0.7, 1.0),
+            };
+            // Cracked walls fade towards the colour of rubble
+            let damage = map.wall_damage.get(&idx).copied().unwrap_or(0);
+            if let Some(durability) = wall_durability(map.materials[idx]) {
+                if damage > 0 {
+                    fg = fg.lerp(
+                        RGB::from_f32(0.6, 0.6, 0.5),
+                        damage as f32 / durability as f32,
+                    );
                 }
             }
-            if map.bloodstains.contains(&idx) {
-                bg = RGB::from_f32(0.75, 0., 0.);
-            }
-            if !map.visible_tiles[idx] {
-                fg = fg.to_greyscale();
-                bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
-            }
-            ctx.set(x, y, fg, bg, glyph);
         }
+        TileType::DownStairs => {
+            glyph = rltk::to_cp437('>');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('<');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::Water => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::from_f32(0.3, 0.6, 1.0);
+        }
+        TileType::DeepWater => {
+            glyph = rltk::to_cp437('≈');
+            fg = RGB::from_f32(0.1, 0.2, 1.0);
+            bg = RGB::from_f32(0., 0., 0.3);
+        }
+        TileType::Lava => {
+            glyph = rltk::to_cp437('≈');
+            fg = RGB::from_f32(1.0, 0.5, 0.);
+            bg = RGB::from_f32(0.4, 0., 0.);
+        }
+        TileType::Grass => {
+            glyph = rltk::to_cp437('"');
+            fg = palette.grass;
+        }
+        TileType::Bridge => {
+            glyph = rltk::to_cp437('=');
+            fg = RGB::from_f32(0.6, 0.4, 0.2);
+        }
+        TileType::Rubble => {
+            glyph = rltk::to_cp437(';');
+            fg = RGB::from_f32(0.6, 0.6, 0.5);
+        }
+        TileType::Chasm => {
+            glyph = rltk::to_cp437('░');
+            fg = RGB::from_f32(0.15, 0.15, 0.2);
+        }
+    }
+    if map.bloodstains.contains(&idx) {
+        bg = RGB::from_f32(0.75, 0., 0.);
+    }
+    if map.visible_tiles[idx] {
+        let tint = light_tint(map, idx);
+        fg = fg * tint;
+        bg = bg * tint;
+    } else {
+        fg = fg.to_greyscale();
+        bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
+    }
+    (glyph, fg, bg)
+}
+
+/// The character a tile is written as in plain-text maps.
+pub fn tile_to_ascii(tile: TileType) -> char {
+    match tile {
+        TileType::Wall => '#',
+        TileType::Floor => '.',
+        TileType::DownStairs => '>',
+        TileType::UpStairs => '<',
+        TileType::Water => '~',
+        TileType::DeepWater => '%',
+        TileType::Lava => '^',
+        TileType::Grass => '"',
+        TileType::Bridge => '=',
+        TileType::Rubble => ';',
+        TileType::Chasm => ':',
+    }
+}
 
-        x += 1;
-        if x > (WIDTH - 1) as i32 {
-            x = 0;
-            y += 1;
+/// The tile a plain-text map character stands for, the reverse of `tile_to_ascii`.
+pub fn ascii_to_tile(c: char) -> Option<TileType> {
+    match c {
+        '#' => Some(TileType::Wall),
+        '.' => Some(TileType::Floor),
+        '>' => Some(TileType::DownStairs),
+        '<' => Some(TileType::UpStairs),
+        '~' => Some(TileType::Water),
+        '%' => Some(TileType::DeepWater),
+        '^' => Some(TileType::Lava),
+        '"' => Some(TileType::Grass),
+        '=' => Some(TileType::Bridge),
+        ';' => Some(TileType::Rubble),
+        ':' => Some(TileType::Chasm),
+        _ => None,
+    }
+}
+
+/// Plain-text rendering of the map's tiles, one string per row.
+pub fn map_to_ascii(map: &Map) -> Vec<String> {
+    let mut lines = Vec::new();
+    for y in 0..map.height {
+        let mut line = String::new();
+        for x in 0..map.width {
+            line.push(tile_to_ascii(map.tiles[map.xy_idxi32(x, y)]));
         }
+        lines.push(line);
     }
+    lines
 }
 
 fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
@@ -178,23 +329,25 @@ fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
 }
 
 impl Map {
-    pub fn idx_to_xy(idx: usize) -> (usize, usize) {
-        let x = idx % WIDTH;
-        let y = idx / WIDTH;
+    pub fn idx_to_xy(&self, idx: usize) -> (i32, i32) {
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
         (x, y)
     }
-    pub fn xy_idx_impl(x: usize, y: usize) -> usize {
-        (y * WIDTH) + x
-    }
 
     pub fn xy_idx(&self, x: usize, y: usize) -> usize {
-        Map::xy_idx_impl(x, y)
+        (y * self.width as usize) + x
     }
 
     pub fn xy_idxi32(&self, x: i32, y: i32) -> usize {
         self::Map::xy_idx(self, x as usize, y as usize)
     }
 
+    /// The cost of a step onto `idx`, scaling the base cost of that direction.
+    fn step_cost(&self, idx: usize, base: f32) -> f32 {
+        base * tile_cost(self.tiles[idx])
+    }
+
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
         if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
             return false;
@@ -203,9 +356,43 @@ impl Map {
         !self.blocked[idx]
     }
 
+    /// Sets what every tile of the map is made of, for builders whose walls aren't stone.
+    pub fn set_material(&mut self, material: WallMaterial) {
+        for tile_material in self.materials.iter_mut() {
+            *tile_material = material;
+        }
+    }
+
+    /// Whether (x, y) is on the edge of the map, which must never be opened up.
+    pub fn is_border(&self, x: i32, y: i32) -> bool {
+        x < 1 || x > self.width - 2 || y < 1 || y > self.height - 2
+    }
+
+    /// Chips `amount` off the wall at `idx`, turning it into rubble once its durability is used
+    /// up. Borders and walls that can't be destroyed are left alone. Returns true if the wall
+    /// collapsed.
+    pub fn damage_wall(&mut self, idx: usize, amount: i32) -> bool {
+        let (x, y) = self.idx_to_xy(idx);
+        if self.tiles[idx] != TileType::Wall || self.is_border(x, y) {
+            return false;
+        }
+        let durability = match wall_durability(self.materials[idx]) {
+            Some(durability) => durability,
+            None => return false,
+        };
+        let damage = self.wall_damage.entry(idx).or_insert(0);
+        *damage += amount;
+        if *damage < durability {
+            return false;
+        }
+        self.wall_damage.remove(&idx);
+        self.tiles[idx] = TileType::Rubble;
+        true
+    }
+
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::Wall;
+            self.blocked[i] = !tile_walkable(*tile);
         }
     }
 
@@ -214,19 +401,82 @@ impl Map {
             content.clear();
         }
     }
-    /// Generates an empty map, consisting entirely of solid walls
-    pub fn new(new_depth: i32) -> Map {
+    pub fn tile_count(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    /// Generates an empty map of the given size, consisting entirely of solid walls
+    pub fn new(new_depth: i32, width: i32, height: i32) -> Map {
+        let tile_count = (width * height) as usize;
         Map {
-            tiles: vec![TileType::Wall; MAP_COUNT],
+            tiles: vec![TileType::Wall; tile_count],
             rooms: Vec::new(),
-            width: WIDTH as i32,
-            height: HEIGHT as i32,
-            revealed_tiles: vec![false; MAP_COUNT],
-            visible_tiles: vec![false; MAP_COUNT],
-            blocked: vec![false; MAP_COUNT],
-            tile_content: vec![Vec::new(); MAP_COUNT],
+            width,
+            height,
+            revealed_tiles: vec![false; tile_count],
+            visible_tiles: vec![false; tile_count],
+            blocked: vec![false; tile_count],
+            tile_content: vec![Vec::new(); tile_count],
             depth: new_depth,
             bloodstains: HashSet::new(),
+            materials: vec![WallMaterial::Stone; tile_count],
+            wall_damage: HashMap::new(),
+            light: vec![RGB::from_f32(1.0, 1.0, 1.0); tile_count],
+            theme: LevelTheme::Dungeon,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_with_wall(material: WallMaterial) -> (Map, usize) {
+        let mut map = Map::new(1, 5, 5);
+        let idx = map.xy_idx(2, 2);
+        map.materials[idx] = material;
+        (map, idx)
+    }
+
+    #[test]
+    fn walls_collapse_once_their_durability_is_used_up() {
+        for material in [WallMaterial::Earth, WallMaterial::Stone] {
+            let durability = wall_durability(material).unwrap();
+            let (mut map, idx) = map_with_wall(material);
+            assert!(!map.damage_wall(idx, durability - 1));
+            assert!(map.tiles[idx] == TileType::Wall);
+            assert!(map.damage_wall(idx, 1));
+            assert!(map.tiles[idx] == TileType::Rubble);
+            assert!(!map.wall_damage.contains_key(&idx));
         }
     }
+
+    #[test]
+    fn earth_gives_way_before_stone() {
+        let earth = wall_durability(WallMaterial::Earth).unwrap();
+        let (mut map, idx) = map_with_wall(WallMaterial::Stone);
+        assert!(!map.damage_wall(idx, earth));
+        assert_eq!(map.wall_damage[&idx], earth);
+    }
+
+    #[test]
+    fn reinforced_walls_and_borders_never_break() {
+        let (mut map, idx) = map_with_wall(WallMaterial::Reinforced);
+        assert!(!map.damage_wall(idx, 1000));
+        assert!(map.tiles[idx] == TileType::Wall);
+
+        let border = map.xy_idx(0, 2);
+        map.materials[border] = WallMaterial::Earth;
+        assert!(!map.damage_wall(border, 1000));
+        assert!(map.tiles[border] == TileType::Wall);
+    }
+
+    #[test]
+    fn only_walls_take_damage() {
+        let (mut map, idx) = map_with_wall(WallMaterial::Earth);
+        map.tiles[idx] = TileType::Floor;
+        assert!(!map.damage_wall(idx, 1000));
+        assert!(map.tiles[idx] == TileType::Floor);
+        assert!(map.wall_damage.is_empty());
+    }
 }
@@ -0,0 +1,63 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, Position, TileType};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum XStart {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum YStart {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// Starts the player on the floor tile closest to one of the map's corners, edges or centre.
+pub struct AreaStartingPosition {
+    x: XStart,
+    y: YStart,
+}
+
+impl MetaMapBuilder for AreaStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let seed_x = match self.x {
+            XStart::Left => 1,
+            XStart::Center => map.width / 2,
+            XStart::Right => map.width - 2,
+        };
+        let seed_y = match self.y {
+            YStart::Top => 1,
+            YStart::Center => map.height / 2,
+            YStart::Bottom => map.height - 2,
+        };
+
+        let seed = rltk::Point::new(seed_x, seed_y);
+        let closest = map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_idx, tile)| **tile == TileType::Floor)
+            .map(|(idx, _tile)| {
+                let (x, y) = map.idx_to_xy(idx);
+                let distance =
+                    rltk::DistanceAlg::PythagorasSquared.distance2d(seed, rltk::Point::new(x, y));
+                (x, y, distance)
+            })
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+
+        if let Some((x, y, _distance)) = closest {
+            build_data.starting_position = Some(Position { x, y });
+        }
+    }
+}
+
+impl AreaStartingPosition {
+    pub fn new(x: XStart, y: YStart) -> Box<AreaStartingPosition> {
+        Box::new(AreaStartingPosition { x, y })
+    }
+}
@@ -0,0 +1,41 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, TileType, WallMaterial};
+
+/// Shelves are broken up by a gap this often, so that rows can be crossed.
+const SHELF_GAP_EVERY: i32 = 6;
+
+/// Lines rooms with rows of wooden shelves (easily broken earth walls), leaving the row by
+/// each wall and an aisle at either end free so that the room stays connected. The first room,
+/// where the player starts, is left as a reading room.
+pub struct Bookshelves {}
+
+impl MetaMapBuilder for Bookshelves {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let rooms = map.rooms.clone();
+        for room in rooms.iter().skip(1) {
+            if room.x2 - room.x1 < 5 || room.y2 - room.y1 < 3 {
+                continue;
+            }
+            for y in (room.y1 + 2..room.y2).step_by(2) {
+                for x in room.x1 + 2..room.x2 {
+                    if (x - room.x1) % SHELF_GAP_EVERY == 0 {
+                        continue;
+                    }
+                    let idx = map.xy_idxi32(x, y);
+                    if map.tiles[idx] == TileType::Floor {
+                        map.tiles[idx] = TileType::Wall;
+                        map.materials[idx] = WallMaterial::Earth;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Bookshelves {
+    pub fn new() -> Box<Bookshelves> {
+        Box::new(Bookshelves {})
+    }
+}
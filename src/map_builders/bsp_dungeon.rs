@@ -0,0 +1,114 @@
+use rltk::RandomNumberGenerator;
+
+use super::{
+    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, BuilderMap,
+    InitialMapBuilder, Map, MapHistory, Rect,
+};
+
+/// Leaves are never split below this size, which keeps every room at least 3x3.
+const MIN_LEAF_SIZE: i32 = 8;
+
+/// Recursively splits the map into halves and carves a room into every leaf. Corridors follow
+/// the partition tree: each split joins a room on one side with a room on the other.
+pub struct BspDungeonBuilder {}
+
+impl InitialMapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let bounds = Rect {
+            x1: 0,
+            y1: 0,
+            x2: map.width - 2,
+            y2: map.height - 2,
+        };
+        let history = &mut build_data.history;
+        map.rooms = BspDungeonBuilder::partition(map, rng, history, &bounds);
+    }
+}
+
+impl BspDungeonBuilder {
+    pub fn new() -> Box<BspDungeonBuilder> {
+        Box::new(BspDungeonBuilder {})
+    }
+
+    /// Returns the rooms carved inside `area`, ordered from the first partition to the last.
+    fn partition(
+        map: &mut Map,
+        rng: &mut RandomNumberGenerator,
+        history: &mut MapHistory,
+        area: &Rect,
+    ) -> Vec<Rect> {
+        let width = area.x2 - area.x1;
+        let height = area.y2 - area.y1;
+        let can_split_x = width >= MIN_LEAF_SIZE * 2;
+        let can_split_y = height >= MIN_LEAF_SIZE * 2;
+
+        if !can_split_x && !can_split_y {
+            let room = BspDungeonBuilder::room_in_leaf(rng, area);
+            apply_room_to_map(map, &room);
+            history.take_snapshot(map);
+            return vec![room];
+        }
+
+        let split_x = if can_split_x && can_split_y {
+            rng.range(0, 2) == 0
+        } else {
+            can_split_x
+        };
+        let (first, second) = if split_x {
+            let split = area.x1 + rng.range(MIN_LEAF_SIZE, width - MIN_LEAF_SIZE + 1);
+            (
+                Rect {
+                    x2: split,
+                    ..area.clone()
+                },
+                Rect {
+                    x1: split,
+                    ..area.clone()
+                },
+            )
+        } else {
+            let split = area.y1 + rng.range(MIN_LEAF_SIZE, height - MIN_LEAF_SIZE + 1);
+            (
+                Rect {
+                    y2: split,
+                    ..area.clone()
+                },
+                Rect {
+                    y1: split,
+                    ..area.clone()
+                },
+            )
+        };
+
+        let mut rooms = BspDungeonBuilder::partition(map, rng, history, &first);
+        let second_rooms = BspDungeonBuilder::partition(map, rng, history, &second);
+
+        let from = rooms[rng.range(0, rooms.len() as i32) as usize].center();
+        let to = second_rooms[rng.range(0, second_rooms.len() as i32) as usize].center();
+        if rng.range(0, 2) == 1 {
+            apply_horizontal_tunnel(map, from.0, to.0, from.1);
+            apply_vertical_tunnel(map, from.1, to.1, to.0);
+        } else {
+            apply_vertical_tunnel(map, from.1, to.1, from.0);
+            apply_horizontal_tunnel(map, from.0, to.0, to.1);
+        }
+        history.take_snapshot(map);
+
+        rooms.extend(second_rooms);
+        rooms
+    }
+
+    /// A room filling most of the leaf, leaving a wall on the far edges so that rooms in
+    /// neighbouring leaves never merge.
+    fn room_in_leaf(rng: &mut RandomNumberGenerator, leaf: &Rect) -> Rect {
+        let width = leaf.x2 - leaf.x1;
+        let height = leaf.y2 - leaf.y1;
+        Rect {
+            x1: leaf.x1 + rng.range(0, width / 4 + 1),
+            y1: leaf.y1 + rng.range(0, height / 4 + 1),
+            x2: leaf.x2 - 1 - rng.range(0, width / 4 + 1),
+            y2: leaf.y2 - 1 - rng.range(0, height / 4 + 1),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, InitialMapBuilder, Map, TileType, WallMaterial};
+
+const SMOOTHING_ITERATIONS: i32 = 15;
+
+/// Natural looking caves: random noise smoothed into open caverns.
+pub struct CellularAutomataBuilder {}
+
+impl InitialMapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        map.set_material(WallMaterial::Earth);
+
+        // Roughly 55% floor to start with, leaving the border solid
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idxi32(x, y);
+                map.tiles[idx] = if rng.roll_dice(1, 100) > 55 {
+                    TileType::Floor
+                } else {
+                    TileType::Wall
+                };
+            }
+        }
+        build_data.history.take_snapshot(map);
+
+        for _i in 0..SMOOTHING_ITERATIONS {
+            CellularAutomataBuilder::smooth(map);
+            build_data.history.take_snapshot(map);
+        }
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new() -> Box<CellularAutomataBuilder> {
+        Box::new(CellularAutomataBuilder {})
+    }
+
+    /// One step of the automaton: a tile becomes wall if it has more than four walls around
+    /// it, or none at all.
+    fn smooth(map: &mut Map) {
+        let mut new_tiles = map.tiles.clone();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let mut neighbors = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx != 0 || dy != 0)
+                            && map.tiles[map.xy_idxi32(x + dx, y + dy)] == TileType::Wall
+                        {
+                            neighbors += 1;
+                        }
+                    }
+                }
+                let idx = map.xy_idxi32(x, y);
+                new_tiles[idx] = if neighbors > 4 || neighbors == 0 {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                };
+            }
+        }
+        map.tiles = new_tiles;
+    }
+}
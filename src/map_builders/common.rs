@@ -1,5 +1,6 @@
-use super::{Map, Rect, TileType};
+use super::{tile_walkable, Map, Rect, TileType};
 use std::cmp::{max, min};
+use std::collections::HashMap;
 
 pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
     for y in room.y1 + 1..=room.y2 {
@@ -10,20 +11,182 @@ pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
     }
 }
 
+/// Digs a straight corridor along row `y`. Tunnels are clipped to the map's interior, so they
+/// never cut into the border or wrap around onto another row.
 pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
-    for x in min(x1, x2)..=max(x1, x2) {
+    if y < 1 || y > map.height - 2 {
+        return;
+    }
+    for x in max(1, min(x1, x2))..=min(map.width - 2, max(x1, x2)) {
         let idx = map.xy_idxi32(x, y);
-        if idx > 0 && idx < map.width as usize * map.height as usize {
-            map.tiles[idx as usize] = TileType::Floor;
-        }
+        map.tiles[idx] = TileType::Floor;
     }
 }
 
+/// Digs a straight corridor along column `x`, clipped like `apply_horizontal_tunnel`.
 pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
-    for y in min(y1, y2)..=max(y1, y2) {
+    if x < 1 || x > map.width - 2 {
+        return;
+    }
+    for y in max(1, min(y1, y2))..=min(map.height - 2, max(y1, y2)) {
         let idx = map.xy_idxi32(x, y);
-        if idx > 0 && idx < map.width as usize * map.height as usize {
-            map.tiles[idx as usize] = TileType::Floor;
+        map.tiles[idx] = TileType::Floor;
+    }
+}
+
+/// Turns every walkable tile that can't be reached from `start_idx` back into wall.
+pub fn remove_unreachable_areas(map: &mut Map, start_idx: usize) {
+    let dijkstra_map = distances_from(map, start_idx);
+    for (i, tile) in map.tiles.iter_mut().enumerate() {
+        if tile_walkable(*tile) && dijkstra_map.map[i] == f32::MAX {
+            *tile = TileType::Wall;
+        }
+    }
+}
+
+/// Digs a corridor from every pocket of floor that can't be reached from `start_idx` to the
+/// nearest tile that can, starting with the pocket closest to the reachable area.
+pub fn connect_unreachable_areas(map: &mut Map, start_idx: usize) {
+    loop {
+        let dijkstra_map = distances_from(map, start_idx);
+        let mut reachable: Vec<(i32, i32)> = Vec::new();
+        let mut unreachable: Vec<(i32, i32)> = Vec::new();
+        for (i, tile) in map.tiles.iter().enumerate() {
+            if !tile_walkable(*tile) {
+                continue;
+            }
+            if dijkstra_map.map[i] == f32::MAX {
+                unreachable.push(map.idx_to_xy(i));
+            } else {
+                reachable.push(map.idx_to_xy(i));
+            }
+        }
+        if unreachable.is_empty() || reachable.is_empty() {
+            return;
+        }
+
+        let mut closest = (unreachable[0], reachable[0], i32::MAX);
+        for from in unreachable.iter() {
+            for to in reachable.iter() {
+                let distance = (from.0 - to.0).abs() + (from.1 - to.1).abs();
+                if distance < closest.2 {
+                    closest = (*from, *to, distance);
+                }
+            }
+        }
+        let ((from_x, from_y), (to_x, to_y), _distance) = closest;
+        apply_horizontal_tunnel(map, from_x, to_x, from_y);
+        apply_vertical_tunnel(map, from_y, to_y, to_x);
+    }
+}
+
+/// The reachable floor tile farthest from `start_idx`.
+pub fn most_distant_tile(map: &mut Map, start_idx: usize) -> usize {
+    let dijkstra_map = distances_from(map, start_idx);
+    let mut exit_tile = (start_idx, 0.0f32);
+    for (i, tile) in map.tiles.iter().enumerate() {
+        let distance_to_start = dijkstra_map.map[i];
+        if *tile == TileType::Floor
+            && distance_to_start != f32::MAX
+            && distance_to_start > exit_tile.1
+        {
+            exit_tile = (i, distance_to_start);
+        }
+    }
+    exit_tile.0
+}
+
+/// Walking distance from `start_idx` to every tile, `f32::MAX` where it can't be reached.
+pub fn distances_from(map: &mut Map, start_idx: usize) -> rltk::DijkstraMap {
+    map.populate_blocked();
+    let map_starts: Vec<usize> = vec![start_idx];
+    rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &map_starts,
+        map,
+        // Enough for a path that zigzags across every tile of the map
+        map.tile_count() as f32 * 1.5,
+    )
+}
+
+/// Groups the walkable tiles, other than stairs, into cellular noise regions, used to spread
+/// spawns over the map whatever shape it has.
+pub fn generate_voronoi_spawn_regions(
+    map: &Map,
+    rng: &mut rltk::RandomNumberGenerator,
+) -> HashMap<i32, Vec<usize>> {
+    let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
+    let mut cell_noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    cell_noise.set_noise_type(rltk::NoiseType::Cellular);
+    cell_noise.set_frequency(0.08);
+    cell_noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
+
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idxi32(x, y);
+            let tile = map.tiles[idx];
+            if tile_walkable(tile) && tile != TileType::DownStairs && tile != TileType::UpStairs {
+                let cell_value = (cell_noise.get_noise(x as f32, y as f32) * 10240.0) as i32;
+                noise_areas.entry(cell_value).or_default().push(idx);
+            }
         }
     }
+    noise_areas
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+/// Digs a `brush_size` square of floor at (x, y), mirrored across the map's centre lines
+/// according to `mode`.
+pub fn paint(map: &mut Map, mode: Symmetry, brush_size: i32, x: i32, y: i32) {
+    let center_x = map.width / 2;
+    let center_y = map.height / 2;
+    let mirror_x = if x <= center_x {
+        center_x + (center_x - x)
+    } else {
+        center_x - (x - center_x)
+    };
+    let mirror_y = if y <= center_y {
+        center_y + (center_y - y)
+    } else {
+        center_y - (y - center_y)
+    };
+
+    apply_paint(map, brush_size, x, y);
+    match mode {
+        Symmetry::None => {}
+        Symmetry::Horizontal => apply_paint(map, brush_size, mirror_x, y),
+        Symmetry::Vertical => apply_paint(map, brush_size, x, mirror_y),
+        Symmetry::Both => {
+            apply_paint(map, brush_size, mirror_x, y);
+            apply_paint(map, brush_size, x, mirror_y);
+            apply_paint(map, brush_size, mirror_x, mirror_y);
+        }
+    }
+}
+
+fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
+    let half_brush = brush_size / 2;
+    for brush_y in y - half_brush..y - half_brush + brush_size {
+        for brush_x in x - half_brush..x - half_brush + brush_size {
+            if brush_x > 1 && brush_x < map.width - 1 && brush_y > 1 && brush_y < map.height - 1 {
+                let idx = map.xy_idxi32(brush_x, brush_y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+    }
+}
+
+pub fn floor_tile_count(map: &Map) -> usize {
+    map.tiles
+        .iter()
+        .filter(|tile| **tile == TileType::Floor)
+        .count()
 }
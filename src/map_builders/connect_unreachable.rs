@@ -0,0 +1,24 @@
+use rltk::RandomNumberGenerator;
+
+use super::{connect_unreachable_areas, BuilderMap, MetaMapBuilder};
+
+/// Digs corridors to every floor tile the player can't walk to from the starting position,
+/// for maps where those pockets are worth keeping, such as rooms.
+pub struct ConnectUnreachable {}
+
+impl MetaMapBuilder for ConnectUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Connect unreachable requires a starting position");
+        let start_idx = build_data.map.xy_idxi32(start.x, start.y);
+        connect_unreachable_areas(&mut build_data.map, start_idx);
+    }
+}
+
+impl ConnectUnreachable {
+    pub fn new() -> Box<ConnectUnreachable> {
+        Box::new(ConnectUnreachable {})
+    }
+}
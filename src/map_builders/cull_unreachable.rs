@@ -0,0 +1,23 @@
+use rltk::RandomNumberGenerator;
+
+use super::{remove_unreachable_areas, BuilderMap, MetaMapBuilder};
+
+/// Walls off every floor tile the player can't walk to from the starting position.
+pub struct CullUnreachable {}
+
+impl MetaMapBuilder for CullUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Cull unreachable requires a starting position");
+        let start_idx = build_data.map.xy_idxi32(start.x, start.y);
+        remove_unreachable_areas(&mut build_data.map, start_idx);
+    }
+}
+
+impl CullUnreachable {
+    pub fn new() -> Box<CullUnreachable> {
+        Box::new(CullUnreachable {})
+    }
+}
@@ -0,0 +1,24 @@
+use rltk::RandomNumberGenerator;
+
+use super::{most_distant_tile, BuilderMap, MetaMapBuilder, TileType};
+
+/// Puts the down stairs on the reachable tile farthest from the starting position.
+pub struct DistantExit {}
+
+impl MetaMapBuilder for DistantExit {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .expect("Distant exit requires a starting position");
+        let start_idx = build_data.map.xy_idxi32(start.x, start.y);
+        let exit_idx = most_distant_tile(&mut build_data.map, start_idx);
+        build_data.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+}
+
+impl DistantExit {
+    pub fn new() -> Box<DistantExit> {
+        Box::new(DistantExit {})
+    }
+}
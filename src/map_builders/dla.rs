@@ -0,0 +1,161 @@
+use rltk::{Point, RandomNumberGenerator};
+
+use super::{
+    floor_tile_count, paint, BuilderMap, InitialMapBuilder, Map, Symmetry, TileType, WallMaterial,
+};
+
+/// Each digger only adds a tile or two, so the map is recorded every this many diggers.
+const SNAPSHOT_INTERVAL: i32 = 20;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DlaAlgorithm {
+    /// Diggers start anywhere and wander until they bump into the existing floor.
+    WalkInwards,
+    /// Diggers start in the middle and wander until they leave the existing floor.
+    WalkOutwards,
+    /// Diggers start anywhere and head straight for the middle.
+    CentralAttractor,
+}
+
+#[derive(Copy, Clone)]
+pub struct DlaSettings {
+    pub algorithm: DlaAlgorithm,
+    pub brush_size: i32,
+    pub symmetry: Symmetry,
+    pub floor_percent: f32,
+    /// The shallowest depth this preset is used on.
+    pub min_depth: i32,
+}
+
+pub const DLA_PRESETS: [DlaSettings; 4] = [
+    // Walk inwards
+    DlaSettings {
+        algorithm: DlaAlgorithm::WalkInwards,
+        brush_size: 1,
+        symmetry: Symmetry::None,
+        floor_percent: 0.25,
+        min_depth: 1,
+    },
+    // Walk outwards
+    DlaSettings {
+        algorithm: DlaAlgorithm::WalkOutwards,
+        brush_size: 2,
+        symmetry: Symmetry::None,
+        floor_percent: 0.25,
+        min_depth: 1,
+    },
+    // Central attractor
+    DlaSettings {
+        algorithm: DlaAlgorithm::CentralAttractor,
+        brush_size: 2,
+        symmetry: Symmetry::None,
+        floor_percent: 0.25,
+        min_depth: 2,
+    },
+    // Insectoid
+    DlaSettings {
+        algorithm: DlaAlgorithm::CentralAttractor,
+        brush_size: 2,
+        symmetry: Symmetry::Horizontal,
+        floor_percent: 0.25,
+        min_depth: 3,
+    },
+];
+
+/// Diffusion-limited aggregation: the level grows one wandering digger at a time, each one
+/// sticking to the floor carved before it.
+pub struct DlaBuilder {
+    settings: DlaSettings,
+}
+
+impl InitialMapBuilder for DlaBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let settings = self.settings;
+        let map = &mut build_data.map;
+        map.set_material(WallMaterial::Earth);
+
+        // Seed a small cross in the middle for the diggers to stick to
+        let start = Point::new(map.width / 2, map.height / 2);
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+            let idx = map.xy_idxi32(start.x + dx, start.y + dy);
+            map.tiles[idx] = TileType::Floor;
+        }
+
+        let desired_floor_tiles = (settings.floor_percent * map.tile_count() as f32) as usize;
+        let mut digger_count = 0;
+        while floor_tile_count(map) < desired_floor_tiles {
+            match settings.algorithm {
+                DlaAlgorithm::WalkInwards => {
+                    let mut digger_x = rng.roll_dice(1, map.width - 3) + 1;
+                    let mut digger_y = rng.roll_dice(1, map.height - 3) + 1;
+                    let mut prev_x = digger_x;
+                    let mut prev_y = digger_y;
+                    while map.tiles[map.xy_idxi32(digger_x, digger_y)] == TileType::Wall {
+                        prev_x = digger_x;
+                        prev_y = digger_y;
+                        DlaBuilder::stagger(map, rng, &mut digger_x, &mut digger_y);
+                    }
+                    paint(map, settings.symmetry, settings.brush_size, prev_x, prev_y);
+                }
+                DlaAlgorithm::WalkOutwards => {
+                    let mut digger_x = start.x;
+                    let mut digger_y = start.y;
+                    while map.tiles[map.xy_idxi32(digger_x, digger_y)] == TileType::Floor {
+                        DlaBuilder::stagger(map, rng, &mut digger_x, &mut digger_y);
+                    }
+                    paint(
+                        map,
+                        settings.symmetry,
+                        settings.brush_size,
+                        digger_x,
+                        digger_y,
+                    );
+                }
+                DlaAlgorithm::CentralAttractor => {
+                    let digger = Point::new(
+                        rng.roll_dice(1, map.width - 3) + 1,
+                        rng.roll_dice(1, map.height - 3) + 1,
+                    );
+                    let mut prev = digger;
+                    let path = rltk::line2d(rltk::LineAlg::Bresenham, digger, start);
+                    for step in path.iter() {
+                        if map.tiles[map.xy_idxi32(step.x, step.y)] == TileType::Floor {
+                            break;
+                        }
+                        prev = *step;
+                    }
+                    paint(map, settings.symmetry, settings.brush_size, prev.x, prev.y);
+                }
+            }
+            digger_count += 1;
+            if digger_count % SNAPSHOT_INTERVAL == 0 {
+                build_data.history.take_snapshot(map);
+            }
+        }
+    }
+}
+
+impl DlaBuilder {
+    pub fn new(settings: DlaSettings) -> Box<DlaBuilder> {
+        Box::new(DlaBuilder { settings })
+    }
+
+    /// Picks one of the presets allowed at this depth.
+    pub fn for_depth(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<DlaBuilder> {
+        let presets: Vec<&DlaSettings> = DLA_PRESETS
+            .iter()
+            .filter(|preset| preset.min_depth <= new_depth)
+            .collect();
+        DlaBuilder::new(*presets[rng.range(0, presets.len() as i32) as usize])
+    }
+
+    /// Moves the digger one step in a random direction, staying off the map's edge.
+    fn stagger(map: &Map, rng: &mut RandomNumberGenerator, x: &mut i32, y: &mut i32) {
+        match rng.roll_dice(1, 4) {
+            1 => *x = i32::max(2, *x - 1),
+            2 => *x = i32::min(map.width - 3, *x + 1),
+            3 => *y = i32::max(2, *y - 1),
+            _ => *y = i32::min(map.height - 3, *y + 1),
+        }
+    }
+}
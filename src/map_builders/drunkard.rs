@@ -0,0 +1,149 @@
+use rltk::RandomNumberGenerator;
+
+use super::{
+    floor_tile_count, paint, BuilderMap, InitialMapBuilder, Symmetry, TileType, WallMaterial,
+};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode {
+    StartingPoint,
+    Random,
+}
+
+#[derive(Copy, Clone)]
+pub struct DrunkardSettings {
+    pub spawn_mode: DrunkSpawnMode,
+    /// How many steps each digger takes before giving up.
+    pub drunken_lifetime: i32,
+    /// Diggers keep coming until this share of the map is floor.
+    pub floor_percent: f32,
+    pub brush_size: i32,
+    pub symmetry: Symmetry,
+    /// The shallowest depth this preset is used on.
+    pub min_depth: i32,
+}
+
+pub const DRUNKARD_PRESETS: [DrunkardSettings; 6] = [
+    // Open area
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::StartingPoint,
+        drunken_lifetime: 400,
+        floor_percent: 0.5,
+        brush_size: 1,
+        symmetry: Symmetry::None,
+        min_depth: 1,
+    },
+    // Open halls
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::Random,
+        drunken_lifetime: 400,
+        floor_percent: 0.5,
+        brush_size: 1,
+        symmetry: Symmetry::None,
+        min_depth: 1,
+    },
+    // Winding passages
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::Random,
+        drunken_lifetime: 100,
+        floor_percent: 0.4,
+        brush_size: 1,
+        symmetry: Symmetry::None,
+        min_depth: 2,
+    },
+    // Fat passages
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::Random,
+        drunken_lifetime: 100,
+        floor_percent: 0.4,
+        brush_size: 2,
+        symmetry: Symmetry::None,
+        min_depth: 3,
+    },
+    // Mirrored passages
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::Random,
+        drunken_lifetime: 100,
+        floor_percent: 0.4,
+        brush_size: 1,
+        symmetry: Symmetry::Vertical,
+        min_depth: 3,
+    },
+    // Fearful symmetry
+    DrunkardSettings {
+        spawn_mode: DrunkSpawnMode::Random,
+        drunken_lifetime: 100,
+        floor_percent: 0.4,
+        brush_size: 1,
+        symmetry: Symmetry::Both,
+        min_depth: 4,
+    },
+];
+
+/// Diggers stumble randomly from a starting point, carving floor wherever they step.
+pub struct DrunkardsWalkBuilder {
+    settings: DrunkardSettings,
+}
+
+impl InitialMapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let settings = self.settings;
+        let map = &mut build_data.map;
+        map.set_material(WallMaterial::Earth);
+
+        let start = (map.width / 2, map.height / 2);
+        let start_idx = map.xy_idxi32(start.0, start.1);
+        map.tiles[start_idx] = TileType::Floor;
+
+        let desired_floor_tiles = (settings.floor_percent * map.tile_count() as f32) as usize;
+        let mut digger_count = 0;
+        while floor_tile_count(map) < desired_floor_tiles {
+            let (mut drunk_x, mut drunk_y) = match settings.spawn_mode {
+                DrunkSpawnMode::StartingPoint => start,
+                DrunkSpawnMode::Random => {
+                    if digger_count == 0 {
+                        start
+                    } else {
+                        (
+                            rng.roll_dice(1, map.width - 3) + 1,
+                            rng.roll_dice(1, map.height - 3) + 1,
+                        )
+                    }
+                }
+            };
+
+            for _step in 0..settings.drunken_lifetime {
+                paint(
+                    map,
+                    settings.symmetry,
+                    settings.brush_size,
+                    drunk_x,
+                    drunk_y,
+                );
+                match rng.roll_dice(1, 4) {
+                    1 => drunk_x = i32::max(2, drunk_x - 1),
+                    2 => drunk_x = i32::min(map.width - 3, drunk_x + 1),
+                    3 => drunk_y = i32::max(2, drunk_y - 1),
+                    _ => drunk_y = i32::min(map.height - 3, drunk_y + 1),
+                }
+            }
+            digger_count += 1;
+            build_data.history.take_snapshot(map);
+        }
+    }
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(settings: DrunkardSettings) -> Box<DrunkardsWalkBuilder> {
+        Box::new(DrunkardsWalkBuilder { settings })
+    }
+
+    /// Picks one of the presets allowed at this depth.
+    pub fn for_depth(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<DrunkardsWalkBuilder> {
+        let presets: Vec<&DrunkardSettings> = DRUNKARD_PRESETS
+            .iter()
+            .filter(|preset| preset.min_depth <= new_depth)
+            .collect();
+        DrunkardsWalkBuilder::new(*presets[rng.range(0, presets.len() as i32) as usize])
+    }
+}
@@ -0,0 +1,46 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, TileType};
+
+const EROSION_PASSES: i32 = 2;
+
+/// Wears down walls that mostly border open floor, rounding off thin walls and jagged edges.
+/// The map's outer border is never touched.
+pub struct ErodeWalls {}
+
+impl MetaMapBuilder for ErodeWalls {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        for _pass in 0..EROSION_PASSES {
+            let mut new_tiles = map.tiles.clone();
+            for y in 1..map.height - 1 {
+                for x in 1..map.width - 1 {
+                    let idx = map.xy_idxi32(x, y);
+                    if map.tiles[idx] != TileType::Wall {
+                        continue;
+                    }
+                    let mut open_neighbors = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            if (dx != 0 || dy != 0)
+                                && map.tiles[map.xy_idxi32(x + dx, y + dy)] != TileType::Wall
+                            {
+                                open_neighbors += 1;
+                            }
+                        }
+                    }
+                    if open_neighbors >= 5 && rng.roll_dice(1, 3) > 1 {
+                        new_tiles[idx] = TileType::Floor;
+                    }
+                }
+            }
+            map.tiles = new_tiles;
+        }
+    }
+}
+
+impl ErodeWalls {
+    pub fn new() -> Box<ErodeWalls> {
+        Box::new(ErodeWalls {})
+    }
+}
@@ -0,0 +1,67 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, TileType};
+
+/// One in this many rooms is flooded.
+const FLOODED_ROOM_CHANCE: i32 = 2;
+/// One in this many other floor tiles gets a puddle.
+const PUDDLE_CHANCE: i32 = 8;
+
+/// Floods rooms: a rim of shallow water that can be waded around a deep pool, with a bridge
+/// across the middle. Leaves the player's room and the room with the stairs dry, and scatters
+/// puddles over the rest of the floor.
+pub struct Flood {}
+
+impl MetaMapBuilder for Flood {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .map(|pos| map.xy_idxi32(pos.x, pos.y));
+
+        let rooms = map.rooms.clone();
+        for room in rooms.iter().skip(1) {
+            let interior: Vec<(i32, i32)> = (room.y1 + 1..=room.y2)
+                .flat_map(|y| (room.x1 + 1..=room.x2).map(move |x| (x, y)))
+                .collect();
+            let has_stairs = interior
+                .iter()
+                .any(|(x, y)| map.tiles[map.xy_idxi32(*x, *y)] == TileType::DownStairs);
+            if has_stairs || rng.roll_dice(1, FLOODED_ROOM_CHANCE) > 1 {
+                continue;
+            }
+
+            let (_center_x, center_y) = room.center();
+            for (x, y) in interior {
+                let idx = map.xy_idxi32(x, y);
+                if map.tiles[idx] != TileType::Floor {
+                    continue;
+                }
+                let rim = x == room.x1 + 1 || x == room.x2 || y == room.y1 + 1 || y == room.y2;
+                map.tiles[idx] = if rim {
+                    TileType::Water
+                } else if y == center_y {
+                    TileType::Bridge
+                } else {
+                    TileType::DeepWater
+                };
+            }
+        }
+
+        for idx in 0..map.tile_count() {
+            if map.tiles[idx] == TileType::Floor
+                && Some(idx) != start
+                && rng.roll_dice(1, PUDDLE_CHANCE) == 1
+            {
+                map.tiles[idx] = TileType::Water;
+            }
+        }
+    }
+}
+
+impl Flood {
+    pub fn new() -> Box<Flood> {
+        Box::new(Flood {})
+    }
+}
@@ -0,0 +1,56 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, TileType};
+
+/// Chance, in percent, of a floor tile starting out covered before the patches are smoothed.
+const FUNGUS_SEED_CHANCE: i32 = 45;
+const SMOOTHING_PASSES: i32 = 3;
+
+/// Covers patches of floor in fungus (grass tiles), using the same smoothing as the cave
+/// generator so that the patches come out as rounded clumps rather than noise.
+pub struct FungusGrowth {}
+
+impl MetaMapBuilder for FungusGrowth {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let mut fungus: Vec<bool> = map
+            .tiles
+            .iter()
+            .map(|tile| *tile == TileType::Floor && rng.roll_dice(1, 100) <= FUNGUS_SEED_CHANCE)
+            .collect();
+
+        for _pass in 0..SMOOTHING_PASSES {
+            let mut new_fungus = fungus.clone();
+            for y in 1..map.height - 1 {
+                for x in 1..map.width - 1 {
+                    let idx = map.xy_idxi32(x, y);
+                    if map.tiles[idx] != TileType::Floor {
+                        continue;
+                    }
+                    let mut neighbors = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            if (dx != 0 || dy != 0) && fungus[map.xy_idxi32(x + dx, y + dy)] {
+                                neighbors += 1;
+                            }
+                        }
+                    }
+                    new_fungus[idx] = neighbors >= 5 || (fungus[idx] && neighbors >= 4);
+                }
+            }
+            fungus = new_fungus;
+        }
+
+        for (idx, covered) in fungus.iter().enumerate() {
+            if *covered && map.tiles[idx] == TileType::Floor {
+                map.tiles[idx] = TileType::Grass;
+            }
+        }
+    }
+}
+
+impl FungusGrowth {
+    pub fn new() -> Box<FungusGrowth> {
+        Box::new(FungusGrowth {})
+    }
+}
@@ -0,0 +1,147 @@
+use rltk::RandomNumberGenerator;
+
+use super::{tile_walkable, BuilderMap, InitialMapBuilder, MetaMapBuilder, TileType};
+
+/// Waiting at the end of every maze, next to the stairs.
+pub const MAZE_REWARD: [&str; 2] = ["Mana Potion", "Mana Potion"];
+
+const TOP: usize = 0;
+const RIGHT: usize = 1;
+const BOTTOM: usize = 2;
+const LEFT: usize = 3;
+
+#[derive(Copy, Clone)]
+struct Cell {
+    walls: [bool; 4],
+    visited: bool,
+}
+
+/// A special level: a recursive backtracker maze carved on a half resolution grid, so every
+/// cell and every opened wall between two cells becomes one tile.
+pub struct MazeBuilder {}
+
+impl InitialMapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let grid_width = (map.width - 2) / 2;
+        let grid_height = (map.height - 2) / 2;
+        let cells = MazeBuilder::generate(grid_width, grid_height, rng);
+
+        for (i, cell) in cells.iter().enumerate() {
+            let x = (i as i32 % grid_width) * 2 + 1;
+            let y = (i as i32 / grid_width) * 2 + 1;
+            let idx = map.xy_idxi32(x, y);
+            map.tiles[idx] = TileType::Floor;
+            if !cell.walls[RIGHT] {
+                let idx = map.xy_idxi32(x + 1, y);
+                map.tiles[idx] = TileType::Floor;
+            }
+            if !cell.walls[BOTTOM] {
+                let idx = map.xy_idxi32(x, y + 1);
+                map.tiles[idx] = TileType::Floor;
+            }
+            if (i as i32 + 1) % grid_width == 0 {
+                build_data.history.take_snapshot(map);
+            }
+        }
+    }
+}
+
+/// Leaves a reward next to the down stairs, for whoever makes it to the end. Each reward gets
+/// its own open tile around the stairs that nothing else spawns on; rewards left over when
+/// there are too few such tiles are dropped.
+pub struct RewardAtExit {
+    rewards: Vec<String>,
+}
+
+impl MetaMapBuilder for RewardAtExit {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let stairs_idx = match map
+            .tiles
+            .iter()
+            .position(|tile| *tile == TileType::DownStairs)
+        {
+            Some(idx) => idx,
+            None => return,
+        };
+        let (x, y) = map.idx_to_xy(stairs_idx);
+        let mut beside_stairs: Vec<usize> = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if x + dx < 0 || x + dx >= map.width || y + dy < 0 || y + dy >= map.height {
+                    continue;
+                }
+                let idx = map.xy_idxi32(x + dx, y + dy);
+                let taken = build_data.spawn_list.iter().any(|spawn| spawn.0 == idx);
+                if tile_walkable(map.tiles[idx]) && idx != stairs_idx && !taken {
+                    beside_stairs.push(idx);
+                }
+            }
+        }
+        for (idx, reward) in beside_stairs.into_iter().zip(self.rewards.iter()) {
+            build_data.spawn_list.push((idx, reward.clone()));
+        }
+    }
+}
+
+impl RewardAtExit {
+    pub fn new(rewards: &[&str]) -> Box<RewardAtExit> {
+        Box::new(RewardAtExit {
+            rewards: rewards.iter().map(|name| name.to_string()).collect(),
+        })
+    }
+}
+
+impl MazeBuilder {
+    pub fn new() -> Box<MazeBuilder> {
+        Box::new(MazeBuilder {})
+    }
+
+    fn generate(width: i32, height: i32, rng: &mut RandomNumberGenerator) -> Vec<Cell> {
+        let mut cells = vec![
+            Cell {
+                walls: [true; 4],
+                visited: false,
+            };
+            (width * height) as usize
+        ];
+        let mut backtrace: Vec<usize> = Vec::new();
+        let mut current = 0usize;
+
+        loop {
+            cells[current].visited = true;
+            let x = current as i32 % width;
+            let y = current as i32 / width;
+
+            let mut neighbors: Vec<(usize, usize)> = Vec::new();
+            if y > 0 && !cells[current - width as usize].visited {
+                neighbors.push((current - width as usize, TOP));
+            }
+            if x < width - 1 && !cells[current + 1].visited {
+                neighbors.push((current + 1, RIGHT));
+            }
+            if y < height - 1 && !cells[current + width as usize].visited {
+                neighbors.push((current + width as usize, BOTTOM));
+            }
+            if x > 0 && !cells[current - 1].visited {
+                neighbors.push((current - 1, LEFT));
+            }
+
+            if neighbors.is_empty() {
+                match backtrace.pop() {
+                    Some(previous) => current = previous,
+                    None => break,
+                }
+            } else {
+                let (next, direction) = neighbors[rng.range(0, neighbors.len() as i32) as usize];
+                cells[current].walls[direction] = false;
+                cells[next].walls[(direction + 2) % 4] = false;
+                backtrace.push(current);
+                current = next;
+            }
+        }
+
+        cells
+    }
+}
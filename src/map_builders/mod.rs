@@ -1,22 +1,391 @@
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
+use area_starting_points::{AreaStartingPosition, XStart, YStart};
+use bookshelves::Bookshelves;
+use bsp_dungeon::BspDungeonBuilder;
+use cellular_automata::CellularAutomataBuilder;
 use common::*;
+use connect_unreachable::ConnectUnreachable;
+use cull_unreachable::CullUnreachable;
+use distant_exit::DistantExit;
+use dla::DlaBuilder;
+use drunkard::DrunkardsWalkBuilder;
+use erode_walls::ErodeWalls;
+use flood::Flood;
+use fungus_growth::FungusGrowth;
+use maze::{MazeBuilder, RewardAtExit};
+use prefab::{PrefabLevelBuilder, PrefabVaultBuilder};
+use room_based_starting_position::RoomBasedStartingPosition;
+use sconce_spawner::SconceSpawner;
 use simple_map::SimpleMapBuilder;
+use validation::validate_map;
+use voronoi_spawning::VoronoiSpawning;
 
-use super::{Map, Position, Rect, spawner, TileType};
+pub use text_map::{level_to_text, spawn_letter, TextLevelBuilder};
 
-mod simple_map;
+use super::{
+    ascii_to_tile, difficulty::Difficulty, map_to_ascii, spawner, themes::LevelTheme,
+    tile_walkable, Map, Position, Rect, TileType, WallMaterial,
+};
+
+mod area_starting_points;
+mod bookshelves;
+mod bsp_dungeon;
+mod cellular_automata;
 mod common;
+mod connect_unreachable;
+mod cull_unreachable;
+mod distant_exit;
+mod dla;
+mod drunkard;
+mod erode_walls;
+mod flood;
+mod fungus_growth;
+mod maze;
+mod prefab;
+mod room_based_starting_position;
+mod sconce_spawner;
+mod simple_map;
+mod text_map;
+mod validation;
+mod voronoi_spawning;
+
+/// Everything a builder chain works on: the map itself, where the player starts and what
+/// gets spawned once the level is done.
+pub struct BuilderMap {
+    pub spawn_list: Vec<(usize, String)>,
+    pub map: Map,
+    pub starting_position: Option<Position>,
+    /// Scales the spawn budgets, see `spawner::EncounterBudget`.
+    pub difficulty: Difficulty,
+    pub history: MapHistory,
+}
+
+/// Snapshots of the map taken while it is being built, for replaying generation on screen.
+/// Nothing is recorded unless `enabled` is set, so normal play doesn't pay for the copies.
+#[derive(Default)]
+pub struct MapHistory {
+    pub enabled: bool,
+    pub snapshots: Vec<Map>,
+}
+
+impl MapHistory {
+    /// Records a copy of the map with every tile revealed and visible.
+    pub fn take_snapshot(&mut self, map: &Map) {
+        if !self.enabled {
+            return;
+        }
+        let mut snapshot = map.clone();
+        for revealed in snapshot.revealed_tiles.iter_mut() {
+            *revealed = true;
+        }
+        for visible in snapshot.visible_tiles.iter_mut() {
+            *visible = true;
+        }
+        self.snapshots.push(snapshot);
+    }
+}
+
+/// Creates the map a chain starts from.
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// Changes the map a previous builder produced.
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// A level recipe: one initial builder followed by any number of meta builders, run in order.
+pub struct BuilderChain {
+    starter: Option<Box<dyn InitialMapBuilder>>,
+    builders: Vec<Box<dyn MetaMapBuilder>>,
+    pub build_data: BuilderMap,
+    /// Why each level thrown away by `build_valid_map` before this one failed validation.
+    pub rejections: Vec<String>,
+}
+
+impl BuilderChain {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> BuilderChain {
+        BuilderChain {
+            starter: None,
+            builders: Vec::new(),
+            build_data: BuilderMap {
+                spawn_list: Vec::new(),
+                map: Map::new(new_depth, width, height),
+                starting_position: None,
+                difficulty: Difficulty::default(),
+                history: MapHistory::default(),
+            },
+            rejections: Vec::new(),
+        }
+    }
+
+    pub fn start_with(&mut self, starter: Box<dyn InitialMapBuilder>) -> &mut BuilderChain {
+        match self.starter {
+            None => self.starter = Some(starter),
+            Some(_) => panic!("You can only have one starting builder."),
+        };
+        self
+    }
+
+    pub fn with(&mut self, metabuilder: Box<dyn MetaMapBuilder>) -> &mut BuilderChain {
+        self.builders.push(metabuilder);
+        self
+    }
+
+    /// Makes the chain keep a snapshot of the map after every step, see `MapHistory`.
+    pub fn record_history(&mut self, enabled: bool) -> &mut BuilderChain {
+        self.build_data.history.enabled = enabled;
+        self
+    }
+
+    pub fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        match &mut self.starter {
+            None => panic!("Cannot run a map builder chain without a starting build system"),
+            Some(starter) => starter.build_map(rng, &mut self.build_data),
+        }
+        let build_data = &mut self.build_data;
+        build_data.history.take_snapshot(&build_data.map);
+
+        for metabuilder in self.builders.iter_mut() {
+            metabuilder.build_map(rng, build_data);
+            build_data.history.take_snapshot(&build_data.map);
+        }
 
-trait MapBuilder {
-    fn build(new_depth: i32) -> (Map, Position);
-    fn spawn(map: &Map, ecs: &mut World, new_depth: i32);
+        // Below the first level, the player arrives on the stairs leading back up
+        if build_data.map.depth > 1 {
+            if let Some(start) = build_data.starting_position.as_ref() {
+                let start_idx = build_data.map.xy_idxi32(start.x, start.y);
+                build_data.map.tiles[start_idx] = TileType::UpStairs;
+                build_data.history.take_snapshot(&build_data.map);
+            }
+        }
+    }
+
+    pub fn spawn_entities(&self, ecs: &mut World) {
+        for entity in self.build_data.spawn_list.iter() {
+            spawner::spawn_entity(ecs, &self.build_data.map, entity);
+        }
+    }
+}
+
+/// Mazes and the hand-drawn keep are special levels, only turning up now and then below the
+/// first depth.
+const MAZE_CHANCE: i32 = 8;
+const PREFAB_LEVEL_CHANCE: i32 = 10;
+/// Chance of a generated level getting a vault stamped into it.
+const VAULT_CHANCE: i32 = 3;
+/// How many levels may be thrown away for failing validation before giving up.
+const MAX_GENERATION_ATTEMPTS: i32 = 20;
+
+/// Builds random levels until one passes validation, falling back on `fallback_builder` if
+/// none does. The reasons for throwing levels away are kept in the result's `rejections`.
+/// `record_history` is passed on to `BuilderChain::record_history`.
+pub fn build_valid_map(
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    difficulty: Difficulty,
+    rng: &mut RandomNumberGenerator,
+    record_history: bool,
+) -> BuilderChain {
+    let mut rejections = Vec::new();
+    for attempt in 1..=MAX_GENERATION_ATTEMPTS {
+        let mut builder = random_builder(new_depth, width, height, difficulty, rng);
+        builder.record_history(record_history).build_map(rng);
+        let problems = validate_map(&mut builder.build_data);
+        if problems.is_empty() {
+            builder.rejections = rejections;
+            return builder;
+        }
+        rejections.push(format!(
+            "Rejected {:?} level at depth {} (attempt {}): {}",
+            builder.build_data.map.theme,
+            new_depth,
+            attempt,
+            problems.join("; ")
+        ));
+    }
+
+    let mut builder = fallback_builder(new_depth, width, height, difficulty);
+    builder.record_history(record_history).build_map(rng);
+    rejections.push(format!(
+        "Used a plain level at depth {} after {} rejected attempts",
+        new_depth, MAX_GENERATION_ATTEMPTS
+    ));
+    builder.rejections = rejections;
+    builder
 }
 
-pub fn spawn(map: &Map, ecs: &mut World, new_depth: i32) {
-    SimpleMapBuilder::spawn(map, ecs, new_depth);
+/// Plain rooms and corridors with anything unreachable filled in, which always passes
+/// validation.
+fn fallback_builder(
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    difficulty: Difficulty,
+) -> BuilderChain {
+    let mut builder = BuilderChain::new(new_depth, width, height);
+    builder.build_data.difficulty = difficulty;
+    builder
+        .start_with(SimpleMapBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(CullUnreachable::new())
+        .with(DistantExit::new())
+        .with(VoronoiSpawning::new())
+        .with(SconceSpawner::new());
+    builder
 }
 
-pub fn build_random_map(new_depth: i32) -> (Map, Position) {
-    SimpleMapBuilder::build(new_depth)
+/// Picks a theme for the level and one of the theme's recipes. Call `build_map` on the result
+/// to actually build it.
+pub fn random_builder(
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    difficulty: Difficulty,
+    rng: &mut RandomNumberGenerator,
+) -> BuilderChain {
+    let theme = LevelTheme::for_depth(new_depth, rng);
+    let mut builder = BuilderChain::new(new_depth, width, height);
+    builder.build_data.map.theme = theme;
+    builder.build_data.difficulty = difficulty;
+    match theme {
+        LevelTheme::Dungeon => dungeon_builder(&mut builder, new_depth, rng),
+        LevelTheme::Crypt => crypt_builder(&mut builder),
+        LevelTheme::FungalCave => fungal_cave_builder(&mut builder),
+        LevelTheme::FloodedRuins => flooded_ruins_builder(&mut builder),
+        LevelTheme::ArcaneLibrary => arcane_library_builder(&mut builder),
+    }
+    builder.with(SconceSpawner::new());
+    builder
+}
+
+/// Any of the generators, with the odd maze, keep or vault.
+fn dungeon_builder(builder: &mut BuilderChain, new_depth: i32, rng: &mut RandomNumberGenerator) {
+    if new_depth > 1 && rng.roll_dice(1, MAZE_CHANCE) == 1 {
+        builder
+            .start_with(MazeBuilder::new())
+            .with(AreaStartingPosition::new(XStart::Left, YStart::Top))
+            .with(CullUnreachable::new())
+            .with(DistantExit::new())
+            .with(RewardAtExit::new(&maze::MAZE_REWARD))
+            .with(VoronoiSpawning::with_table(spawner::maze_table));
+        return;
+    }
+    if new_depth > 1 && rng.roll_dice(1, PREFAB_LEVEL_CHANCE) == 1 {
+        // The keep's garrison is drawn into it, so it is exempt from the encounter budget
+        builder.start_with(PrefabLevelBuilder::keep());
+        return;
+    }
+
+    let room_based = match rng.roll_dice(1, 5) {
+        1 => {
+            builder.start_with(SimpleMapBuilder::new());
+            true
+        }
+        2 => {
+            builder.start_with(BspDungeonBuilder::new());
+            true
+        }
+        3 => {
+            builder.start_with(CellularAutomataBuilder::new());
+            false
+        }
+        4 => {
+            builder.start_with(DrunkardsWalkBuilder::for_depth(new_depth, rng));
+            false
+        }
+        _ => {
+            builder.start_with(DlaBuilder::for_depth(new_depth, rng));
+            false
+        }
+    };
+
+    if room_based {
+        builder
+            .with(RoomBasedStartingPosition::new())
+            .with(ConnectUnreachable::new())
+            .with(DistantExit::new());
+    } else {
+        if rng.roll_dice(1, 2) == 1 {
+            builder.with(ErodeWalls::new());
+        }
+        let (x_start, y_start) = match rng.roll_dice(1, 3) {
+            1 => (XStart::Left, YStart::Top),
+            2 => (XStart::Center, YStart::Center),
+            _ => (XStart::Right, YStart::Bottom),
+        };
+        builder
+            .with(AreaStartingPosition::new(x_start, y_start))
+            .with(CullUnreachable::new())
+            .with(DistantExit::new());
+    }
+
+    // Vaults go first, so their spawns come out of the level's encounter budget
+    if rng.roll_dice(1, VAULT_CHANCE) == 1 {
+        builder.with(PrefabVaultBuilder::new());
+    }
+    builder.with(VoronoiSpawning::new());
+}
+
+/// Tightly packed burial chambers.
+fn crypt_builder(builder: &mut BuilderChain) {
+    builder
+        .start_with(BspDungeonBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(ConnectUnreachable::new())
+        .with(DistantExit::new())
+        .with(VoronoiSpawning::new());
+}
+
+/// Open caverns, overgrown after everything has been placed.
+fn fungal_cave_builder(builder: &mut BuilderChain) {
+    builder
+        .start_with(CellularAutomataBuilder::new())
+        .with(ErodeWalls::new())
+        .with(AreaStartingPosition::new(XStart::Center, YStart::Center))
+        .with(CullUnreachable::new())
+        .with(DistantExit::new())
+        .with(VoronoiSpawning::new())
+        .with(FungusGrowth::new());
+}
+
+/// Rooms and corridors, half sunk.
+fn flooded_ruins_builder(builder: &mut BuilderChain) {
+    builder
+        .start_with(SimpleMapBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(DistantExit::new())
+        .with(Flood::new())
+        .with(ConnectUnreachable::new())
+        .with(VoronoiSpawning::new());
+}
+
+/// Rooms full of shelves.
+fn arcane_library_builder(builder: &mut BuilderChain) {
+    builder
+        .start_with(SimpleMapBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(Bookshelves::new())
+        .with(ConnectUnreachable::new())
+        .with(DistantExit::new())
+        .with(VoronoiSpawning::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_levels_pass_validation() {
+        for seed in 0..20 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = fallback_builder(1 + seed as i32 % 10, 80, 50, Difficulty::Hard);
+            builder.build_map(&mut rng);
+            assert_eq!(validate_map(&mut builder.build_data), Vec::<String>::new());
+        }
+    }
 }
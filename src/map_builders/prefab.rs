@@ -0,0 +1,187 @@
+use rltk::rex::XpFile;
+use rltk::RandomNumberGenerator;
+
+use super::{
+    connect_unreachable_areas, BuilderMap, InitialMapBuilder, Map, MetaMapBuilder, Position,
+    TileType, WallMaterial,
+};
+
+/// A hand-drawn level, used whole.
+const KEEP: &[u8] = include_bytes!("../../resources/keep.xp");
+
+/// Small hand-drawn sections stamped into generated levels.
+const VAULTS: [&[u8]; 2] = [
+    include_bytes!("../../resources/vault_goblin_den.xp"),
+    include_bytes!("../../resources/vault_armory.xp"),
+];
+
+/// A REX Paint drawing read into tiles. Later layers are drawn over earlier ones, skipping
+/// REX Paint's transparent (magenta background) cells.
+struct Prefab {
+    width: i32,
+    height: i32,
+    tiles: Vec<TileType>,
+    start: Option<Position>,
+    spawns: Vec<(i32, i32, &'static str)>,
+}
+
+impl Prefab {
+    fn load(bytes: &[u8]) -> Prefab {
+        let xp = XpFile::read(&mut &bytes[..]).expect("Unable to read REX Paint file");
+        let width = xp.layers.iter().map(|layer| layer.width).max().unwrap_or(0) as i32;
+        let height = xp
+            .layers
+            .iter()
+            .map(|layer| layer.height)
+            .max()
+            .unwrap_or(0) as i32;
+        let mut prefab = Prefab {
+            width,
+            height,
+            tiles: vec![TileType::Wall; (width * height) as usize],
+            start: None,
+            spawns: Vec::new(),
+        };
+
+        for layer in xp.layers.iter() {
+            for y in 0..layer.height {
+                for x in 0..layer.width {
+                    let cell = layer.get(x, y).unwrap();
+                    if cell.bg.r == 255 && cell.bg.g == 0 && cell.bg.b == 255 {
+                        continue;
+                    }
+                    let (x, y) = (x as i32, y as i32);
+                    let (tile, spawn) = read_glyph(cell.ch);
+                    prefab.tiles[(y * width + x) as usize] = tile;
+                    prefab.spawns.retain(|spawn| spawn.0 != x || spawn.1 != y);
+                    match spawn {
+                        Some("Player") => prefab.start = Some(Position { x, y }),
+                        Some(name) => prefab.spawns.push((x, y, name)),
+                        None => {}
+                    }
+                }
+            }
+        }
+        prefab
+    }
+}
+
+/// The tile a glyph stands for, and the entity it places there if it is a spawn marker.
+/// Unknown glyphs are read as floor.
+fn read_glyph(glyph: u32) -> (TileType, Option<&'static str>) {
+    match glyph {
+        219 => (TileType::Wall, None),                 // Solid block
+        173 => (TileType::Floor, Some("Mana Potion")), // ¡
+        _ => match std::char::from_u32(glyph).unwrap_or(' ') {
+            '#' => (TileType::Wall, None),
+            '>' => (TileType::DownStairs, None),
+            '<' => (TileType::UpStairs, None),
+            '@' => (TileType::Floor, Some("Player")),
+            'g' => (TileType::Floor, Some("Goblin")),
+            'o' => (TileType::Floor, Some("Orc")),
+            '!' => (TileType::Floor, Some("Health Potion")),
+            '?' => (TileType::Floor, Some("Magic Missile Scroll")),
+            '/' => (TileType::Floor, Some("Dagger")),
+            ')' => (TileType::Floor, Some("Shield")),
+            _ => (TileType::Floor, None),
+        },
+    }
+}
+
+/// Builds the whole level from a REX Paint file. The level has the drawing's size rather than
+/// the requested one.
+pub struct PrefabLevelBuilder {
+    template: &'static [u8],
+}
+
+impl InitialMapBuilder for PrefabLevelBuilder {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let prefab = Prefab::load(self.template);
+        let mut map = Map::new(build_data.map.depth, prefab.width, prefab.height);
+        map.tiles = prefab.tiles;
+
+        build_data.starting_position = Some(prefab.start.unwrap_or(Position {
+            x: map.width / 2,
+            y: map.height / 2,
+        }));
+        for (x, y, name) in prefab.spawns {
+            build_data
+                .spawn_list
+                .push((map.xy_idxi32(x, y), name.to_string()));
+        }
+        map.theme = build_data.map.theme;
+        build_data.map = map;
+    }
+}
+
+impl PrefabLevelBuilder {
+    pub fn keep() -> Box<PrefabLevelBuilder> {
+        Box::new(PrefabLevelBuilder { template: KEEP })
+    }
+}
+
+/// Stamps a random vault, with unbreakable walls, onto a spot of the map that is entirely open
+/// floor, away from the player's start. Anything already set to spawn under the vault is replaced by the vault's
+/// own spawns, and corridors are dug to anything the vault's walls cut off. Does nothing if no
+/// vault fits.
+pub struct PrefabVaultBuilder {}
+
+impl MetaMapBuilder for PrefabVaultBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let vault = Prefab::load(VAULTS[rng.range(0, VAULTS.len() as i32) as usize]);
+        let map = &mut build_data.map;
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .map(|pos| map.xy_idxi32(pos.x, pos.y));
+
+        let mut candidates: Vec<(i32, i32)> = Vec::new();
+        for y in 1..map.height - vault.height {
+            for x in 1..map.width - vault.width {
+                let fits = (0..vault.height).all(|vy| {
+                    (0..vault.width).all(|vx| {
+                        let idx = map.xy_idxi32(x + vx, y + vy);
+                        map.tiles[idx] == TileType::Floor && Some(idx) != start
+                    })
+                });
+                if fits {
+                    candidates.push((x, y));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return;
+        }
+
+        let (x, y) = candidates[rng.range(0, candidates.len() as i32) as usize];
+        let mut footprint: Vec<usize> = Vec::new();
+        for vy in 0..vault.height {
+            for vx in 0..vault.width {
+                let idx = map.xy_idxi32(x + vx, y + vy);
+                map.tiles[idx] = vault.tiles[(vy * vault.width + vx) as usize];
+                if map.tiles[idx] == TileType::Wall {
+                    map.materials[idx] = WallMaterial::Reinforced;
+                }
+                footprint.push(idx);
+            }
+        }
+        build_data
+            .spawn_list
+            .retain(|(idx, _name)| !footprint.contains(idx));
+        for (vx, vy, name) in vault.spawns.iter() {
+            let idx = map.xy_idxi32(x + vx, y + vy);
+            build_data.spawn_list.push((idx, name.to_string()));
+        }
+
+        // The vault's walls may close off its own doorways or cut through a passage
+        if let Some(start) = start {
+            connect_unreachable_areas(map, start);
+        }
+    }
+}
+
+impl PrefabVaultBuilder {
+    pub fn new() -> Box<PrefabVaultBuilder> {
+        Box::new(PrefabVaultBuilder {})
+    }
+}
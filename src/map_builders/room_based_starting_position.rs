@@ -0,0 +1,24 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, Position};
+
+/// Starts the player in the middle of the first room.
+pub struct RoomBasedStartingPosition {}
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let (x, y) = build_data
+            .map
+            .rooms
+            .first()
+            .expect("Room based starting position requires rooms")
+            .center();
+        build_data.starting_position = Some(Position { x, y });
+    }
+}
+
+impl RoomBasedStartingPosition {
+    pub fn new() -> Box<RoomBasedStartingPosition> {
+        Box::new(RoomBasedStartingPosition {})
+    }
+}
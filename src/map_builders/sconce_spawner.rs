@@ -0,0 +1,53 @@
+use rltk::RandomNumberGenerator;
+
+use super::{BuilderMap, MetaMapBuilder, TileType};
+
+/// Sconces on the first level; every level below gets one fewer, down to `MIN_SCONCES`.
+const MAX_SCONCES: i32 = 12;
+const MIN_SCONCES: i32 = 1;
+
+/// Puts sconces on floor tiles against a wall, fewer the deeper the level, so that light gets
+/// scarce on the way down. Keeps clear of the player's start and of other spawns.
+pub struct SconceSpawner {}
+
+impl MetaMapBuilder for SconceSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .map(|pos| map.xy_idxi32(pos.x, pos.y));
+
+        let mut candidates: Vec<usize> = Vec::new();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idxi32(x, y);
+                let against_wall = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .iter()
+                    .any(|(dx, dy)| map.tiles[map.xy_idxi32(x + dx, y + dy)] == TileType::Wall);
+                if map.tiles[idx] == TileType::Floor
+                    && against_wall
+                    && Some(idx) != start
+                    && !build_data.spawn_list.iter().any(|spawn| spawn.0 == idx)
+                {
+                    candidates.push(idx);
+                }
+            }
+        }
+
+        let count = i32::max(MIN_SCONCES, MAX_SCONCES - map.depth + 1);
+        for _i in 0..count {
+            if candidates.is_empty() {
+                break;
+            }
+            let idx = candidates.remove(rng.range(0, candidates.len() as i32) as usize);
+            build_data.spawn_list.push((idx, "Sconce".to_string()));
+        }
+    }
+}
+
+impl SconceSpawner {
+    pub fn new() -> Box<SconceSpawner> {
+        Box::new(SconceSpawner {})
+    }
+}
@@ -1,36 +1,30 @@
 use rltk::RandomNumberGenerator;
-use specs::prelude::*;
 
 use super::{
-    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, spawner, Map, MapBuilder,
-    Position, Rect, TileType,
+    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, BuilderMap,
+    InitialMapBuilder, Rect,
 };
 
 pub struct SimpleMapBuilder {}
 
-impl MapBuilder for SimpleMapBuilder {
-    fn build(new_depth: i32) -> (Map, Position) {
-        let mut map = Map::new(new_depth);
-        let playerpos = SimpleMapBuilder::rooms_and_corridors(&mut map);
-        (map, playerpos)
-    }
-
-    fn spawn(map: &Map, ecs: &mut World, depth: i32) {
-        for room in map.rooms.iter().skip(1) {
-            spawner::spawn_room(ecs, room, 1);
-        }
+impl InitialMapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        SimpleMapBuilder::rooms_and_corridors(rng, build_data);
     }
 }
 
 impl SimpleMapBuilder {
-    fn rooms_and_corridors(map: &mut Map) -> Position {
+    pub fn new() -> Box<SimpleMapBuilder> {
+        Box::new(SimpleMapBuilder {})
+    }
+
+    fn rooms_and_corridors(rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
         const MAX_ROOMS: i32 = 30;
         const MIN_SIZE: i32 = 6;
         const MAX_SIZE: i32 = 10;
 
-        let mut rng = RandomNumberGenerator::new();
-
-        for i in 0..MAX_ROOMS {
+        let map = &mut build_data.map;
+        for _i in 0..MAX_ROOMS {
             let w = rng.range(MIN_SIZE, MAX_SIZE);
             let h = rng.range(MIN_SIZE, MAX_SIZE);
             let x = rng.roll_dice(1, map.width - w - 1) - 1;
@@ -58,17 +52,8 @@ impl SimpleMapBuilder {
                 }
 
                 map.rooms.push(new_room);
+                build_data.history.take_snapshot(map);
             }
         }
-
-        let stairs_position = map.rooms[map.rooms.len() - 1].center();
-        let stairs_idx = map.xy_idxi32(stairs_position.0, stairs_position.1);
-        map.tiles[stairs_idx] = TileType::DownStairs;
-
-        let start_pos = map.rooms[0].center();
-        Position {
-            x: start_pos.0,
-            y: start_pos.1,
-        }
     }
 }
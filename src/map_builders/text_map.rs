@@ -0,0 +1,289 @@
+use std::fs;
+
+use rltk::RandomNumberGenerator;
+
+use super::{
+    ascii_to_tile, map_to_ascii, spawner, BuilderMap, InitialMapBuilder, Map, Position, TileType,
+};
+
+/// The letter each spawn table entry is written as in plain-text maps. The player's start is
+/// written as `@`. Spawn letters always stand on floor; anything standing on another tile is
+/// listed after the map instead, see `level_to_text`.
+const SPAWN_LETTERS: [(char, &str); 18] = [
+    ('g', "Goblin"),
+    ('o', "Orc"),
+    ('S', "Skeleton"),
+    ('y', "Myconid"),
+    ('w', "Bog Lurker"),
+    ('a', "Animated Tome"),
+    ('h', "Health Potion"),
+    ('m', "Mana Potion"),
+    ('f', "Fireball Scroll"),
+    ('c', "Confusion Scroll"),
+    ('b', "Magic Missile Scroll"),
+    ('M', "Magic Mapping Scroll"),
+    ('T', "Telepathy Scroll"),
+    ('D', "Item Detection Scroll"),
+    ('d', "Dagger"),
+    ('s', "Shield"),
+    ('t', "Torch"),
+    ('l', "Sconce"),
+];
+
+pub fn spawn_letter(name: &str) -> Option<char> {
+    SPAWN_LETTERS
+        .iter()
+        .find(|(_letter, spawn)| *spawn == name)
+        .map(|(letter, _spawn)| *letter)
+}
+
+fn letter_spawn(letter: char) -> Option<&'static str> {
+    SPAWN_LETTERS
+        .iter()
+        .find(|(spawn_letter, _spawn)| *spawn_letter == letter)
+        .map(|(_letter, spawn)| *spawn)
+}
+
+/// Writes a level as plain text, one string per row: the tiles as in `map_to_ascii`, with a
+/// letter for each spawn standing on floor and `@` for the start. Anything else, such as a
+/// goblin on a bridge, the player on the stairs or a spawn without a letter, keeps its tile
+/// and is listed after a blank line as `x y name`, with `@` as the start's name.
+pub fn level_to_text(
+    map: &Map,
+    start: Option<&Position>,
+    spawns: &[(usize, String)],
+) -> Vec<String> {
+    let mut rows: Vec<Vec<char>> = map_to_ascii(map)
+        .iter()
+        .map(|line| line.chars().collect())
+        .collect();
+    let mut lettered = vec![false; map.tile_count()];
+    let mut placements = Vec::new();
+    let mut place = |idx: usize, letter: Option<char>, name: &str| {
+        let (x, y) = map.idx_to_xy(idx);
+        match letter {
+            Some(letter) if map.tiles[idx] == TileType::Floor && !lettered[idx] => {
+                rows[y as usize][x as usize] = letter;
+                lettered[idx] = true;
+            }
+            _ => placements.push(format!("{} {} {}", x, y, name)),
+        }
+    };
+    if let Some(start) = start {
+        place(map.xy_idxi32(start.x, start.y), Some('@'), "@");
+    }
+    for (idx, name) in spawns.iter() {
+        place(*idx, spawn_letter(name), name);
+    }
+
+    let mut lines: Vec<String> = rows
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect();
+    if !placements.is_empty() {
+        lines.push(String::new());
+        lines.append(&mut placements);
+    }
+    lines
+}
+
+/// Builds the whole level from a plain-text map, as written by `level_to_text`. The level is
+/// as wide as the longest row, with shorter rows padded out with wall. The map is checked when
+/// it is parsed, so a bad file is reported before any level is built.
+pub struct TextLevelBuilder {
+    width: i32,
+    height: i32,
+    tiles: Vec<TileType>,
+    start: Option<Position>,
+    spawns: Vec<(i32, i32, String)>,
+}
+
+impl InitialMapBuilder for TextLevelBuilder {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let mut map = Map::new(build_data.map.depth, self.width, self.height);
+        map.tiles = self.tiles.clone();
+        for (x, y, name) in self.spawns.iter() {
+            build_data
+                .spawn_list
+                .push((map.xy_idxi32(*x, *y), name.clone()));
+        }
+        build_data.starting_position = Some(self.start.clone().unwrap_or(Position {
+            x: map.width / 2,
+            y: map.height / 2,
+        }));
+        map.theme = build_data.map.theme;
+        build_data.map = map;
+    }
+}
+
+impl TextLevelBuilder {
+    /// Reads a plain-text map, or describes the first problem with it.
+    pub fn parse(text: &str) -> Result<Box<TextLevelBuilder>, String> {
+        let mut lines = text.lines();
+        let rows: Vec<&str> = lines.by_ref().take_while(|line| !line.is_empty()).collect();
+        if rows.is_empty() {
+            return Err("Text map is empty".to_string());
+        }
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut builder = TextLevelBuilder {
+            width: width as i32,
+            height: rows.len() as i32,
+            tiles: vec![TileType::Wall; width * rows.len()],
+            start: None,
+            spawns: Vec::new(),
+        };
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let (x, y) = (x as i32, y as i32);
+                builder.tiles[(y * builder.width + x) as usize] = match ascii_to_tile(c) {
+                    Some(tile) => tile,
+                    None if c == '@' => {
+                        builder.start = Some(Position { x, y });
+                        TileType::Floor
+                    }
+                    None => match letter_spawn(c) {
+                        Some(name) => {
+                            builder.spawns.push((x, y, name.to_string()));
+                            TileType::Floor
+                        }
+                        None => {
+                            return Err(format!(
+                                "Unknown character '{}' at {},{} in text map",
+                                c, x, y
+                            ))
+                        }
+                    },
+                };
+            }
+        }
+
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (x, y, name) = builder.parse_placement(line)?;
+            if name == "@" {
+                builder.start = Some(Position { x, y });
+            } else {
+                builder.spawns.push((x, y, name));
+            }
+        }
+        Ok(Box::new(builder))
+    }
+
+    pub fn from_file(path: &str) -> Result<Box<TextLevelBuilder>, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read text map {}: {}", path, err))?;
+        TextLevelBuilder::parse(&text)
+    }
+
+    /// Reads an `x y name` line from after the map.
+    fn parse_placement(&self, line: &str) -> Result<(i32, i32, String), String> {
+        let bad_line = || format!("Expected 'x y name' after the text map, got '{}'", line);
+        let mut parts = line.trim().splitn(3, ' ');
+        let x = parts.next().and_then(|x| x.parse::<i32>().ok());
+        let y = parts.next().and_then(|y| y.parse::<i32>().ok());
+        let (x, y, name) = match (x, y, parts.next()) {
+            (Some(x), Some(y), Some(name)) => (x, y, name.trim()),
+            _ => return Err(bad_line()),
+        };
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return Err(format!("{},{} is outside the text map", x, y));
+        }
+        if name != "@" && !spawner::SPAWNABLE_NAMES.contains(&name) {
+            return Err(format!(
+                "Unknown spawn '{}' at {},{} in text map",
+                name, x, y
+            ));
+        }
+        Ok((x, y, name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::BuilderChain;
+
+    const FIXTURE: &str = r#"#########
+#@..g..h#
+#.~~=~~.#
+#..;.."o>
+#########
+
+2 2 Goblin
+4 2 Orc
+8 3 Myconid"#;
+
+    fn load(text: &str) -> BuilderChain {
+        let mut builder = BuilderChain::new(1, 0, 0);
+        builder
+            .start_with(TextLevelBuilder::parse(text).expect("Fixture should parse"))
+            .build_map(&mut RandomNumberGenerator::seeded(1));
+        builder
+    }
+
+    fn text_of(builder: &BuilderChain) -> Vec<String> {
+        let build_data = &builder.build_data;
+        level_to_text(
+            &build_data.map,
+            build_data.starting_position.as_ref(),
+            &build_data.spawn_list,
+        )
+    }
+
+    #[test]
+    fn fixture_round_trips() {
+        let builder = load(FIXTURE);
+        let map = &builder.build_data.map;
+        assert_eq!((map.width, map.height), (9, 5));
+        assert!(map.tiles[map.xy_idx(4, 2)] == TileType::Bridge);
+        assert!(map.tiles[map.xy_idx(8, 3)] == TileType::DownStairs);
+        assert_eq!(
+            builder.build_data.spawn_list,
+            vec![
+                (map.xy_idx(4, 1), "Goblin".to_string()),
+                (map.xy_idx(7, 1), "Health Potion".to_string()),
+                (map.xy_idx(7, 3), "Orc".to_string()),
+                (map.xy_idx(2, 2), "Goblin".to_string()),
+                (map.xy_idx(4, 2), "Orc".to_string()),
+                (map.xy_idx(8, 3), "Myconid".to_string()),
+            ]
+        );
+        assert_eq!(text_of(&builder), FIXTURE.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn start_on_stairs_keeps_the_stairs() {
+        let builder = load("#####\n#.g>#\n#####\n\n3 1 @");
+        let text = text_of(&builder);
+        assert_eq!(text, vec!["#####", "#.g>#", "#####", "", "3 1 @"]);
+    }
+
+    #[test]
+    fn bad_maps_are_reported() {
+        let error = |text: &str| TextLevelBuilder::parse(text).err();
+        assert_eq!(error(""), Some("Text map is empty".to_string()));
+        assert_eq!(
+            error("###\n#X#\n###"),
+            Some("Unknown character 'X' at 1,1 in text map".to_string())
+        );
+        assert_eq!(
+            error("###\n#.#\n###\n\n1 1 Dragon"),
+            Some("Unknown spawn 'Dragon' at 1,1 in text map".to_string())
+        );
+        assert_eq!(
+            error("###\n#.#\n###\n\n5 1 Orc"),
+            Some("5,1 is outside the text map".to_string())
+        );
+        assert!(error("###\n#.#\n###\n\norc").is_some());
+    }
+
+    #[test]
+    fn missing_file_is_reported() {
+        let error = TextLevelBuilder::from_file("no/such/map.txt").err();
+        assert!(error.is_some_and(|error| error.starts_with("Unable to read text map")));
+    }
+}
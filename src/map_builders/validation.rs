@@ -0,0 +1,89 @@
+use super::{distances_from, tile_walkable, BuilderMap, TileType};
+
+/// Checks that a finished level is playable: the player starts on open ground inside the map,
+/// and the down stairs and every spawn can be walked to from there. Returns a description of
+/// each problem found, or nothing if the level is fine.
+pub fn validate_map(build_data: &mut BuilderMap) -> Vec<String> {
+    let map = &mut build_data.map;
+    let start = match build_data.starting_position.as_ref() {
+        Some(start) => start,
+        None => return vec!["no starting position".to_string()],
+    };
+    if start.x < 1 || start.x > map.width - 2 || start.y < 1 || start.y > map.height - 2 {
+        return vec![format!("start {},{} is outside the map", start.x, start.y)];
+    }
+    let start_idx = map.xy_idxi32(start.x, start.y);
+    if !tile_walkable(map.tiles[start_idx]) {
+        return vec![format!("start {},{} is not walkable", start.x, start.y)];
+    }
+
+    let mut problems = Vec::new();
+    let dijkstra_map = distances_from(map, start_idx);
+    let reachable = |idx: usize| idx < dijkstra_map.map.len() && dijkstra_map.map[idx] != f32::MAX;
+
+    let stairs: Vec<usize> = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_idx, tile)| **tile == TileType::DownStairs)
+        .map(|(idx, _tile)| idx)
+        .collect();
+    if stairs.is_empty() {
+        problems.push("no down stairs".to_string());
+    } else if !stairs.iter().any(|idx| reachable(*idx)) {
+        let (x, y) = map.idx_to_xy(stairs[0]);
+        problems.push(format!("down stairs at {},{} can't be reached", x, y));
+    }
+
+    for (idx, name) in build_data.spawn_list.iter() {
+        if !reachable(*idx) {
+            let (x, y) = map.idx_to_xy(*idx);
+            problems.push(format!("{} at {},{} can't be reached", name, x, y));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::{BuilderChain, TextLevelBuilder};
+    use rltk::RandomNumberGenerator;
+
+    fn problems(text: &str) -> Vec<String> {
+        let mut builder = BuilderChain::new(1, 0, 0);
+        builder
+            .start_with(TextLevelBuilder::parse(text).expect("Fixture should parse"))
+            .build_map(&mut RandomNumberGenerator::seeded(1));
+        validate_map(&mut builder.build_data)
+    }
+
+    #[test]
+    fn connected_level_passes() {
+        assert_eq!(problems("#######\n#@.g.>#\n#######"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn walled_off_stairs_are_rejected() {
+        assert_eq!(
+            problems("#######\n#@.#.>#\n#######"),
+            vec!["down stairs at 5,1 can't be reached".to_string()]
+        );
+    }
+
+    #[test]
+    fn spawn_in_unreachable_pocket_is_rejected() {
+        assert_eq!(
+            problems("#########\n#@..>#.g#\n#########"),
+            vec!["Goblin at 7,1 can't be reached".to_string()]
+        );
+    }
+
+    #[test]
+    fn missing_stairs_are_rejected() {
+        assert_eq!(
+            problems("#####\n#@.g#\n#####"),
+            vec!["no down stairs".to_string()]
+        );
+    }
+}
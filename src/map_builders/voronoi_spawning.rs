@@ -0,0 +1,65 @@
+use rltk::RandomNumberGenerator;
+
+use super::spawner::{self, EncounterBudget};
+use super::{generate_voronoi_spawn_regions, BuilderMap, MetaMapBuilder};
+use crate::random_table::RandomTable;
+
+/// Splits the walkable tiles into noise regions and shares the level's encounter budget out
+/// between them by size, so spawns spread evenly over any kind of map. Points a region can't
+/// spend carry over to the next one. Rolls on the level theme's spawn table unless given a
+/// table of its own. Anything already in the spawn list, such as a vault's contents, is paid
+/// for first and its tiles are left alone. Never spawns on the stairs or the player's start.
+pub struct VoronoiSpawning {
+    table: Option<fn(i32) -> RandomTable>,
+}
+
+impl MetaMapBuilder for VoronoiSpawning {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let spawn_table = match self.table {
+            Some(table) => table(map.depth),
+            None => spawner::themed_table(map.theme, map.depth),
+        };
+        let start = build_data
+            .starting_position
+            .as_ref()
+            .map(|pos| map.xy_idxi32(pos.x, pos.y));
+        let mut regions = generate_voronoi_spawn_regions(map, rng);
+        let taken: Vec<usize> = build_data.spawn_list.iter().map(|spawn| spawn.0).collect();
+        for area in regions.values_mut() {
+            area.retain(|idx| Some(*idx) != start && !taken.contains(idx));
+        }
+
+        let level_budget = EncounterBudget::for_level(map.depth, build_data.difficulty)
+            .minus(&EncounterBudget::spent_on(&build_data.spawn_list));
+        let total_tiles: usize = regions.values().map(Vec::len).sum();
+        let mut covered_tiles = 0;
+        let mut spent = EncounterBudget::default();
+        for area in regions.values() {
+            covered_tiles += area.len();
+            let allowance = level_budget
+                .portion(covered_tiles, total_tiles)
+                .minus(&spent);
+            let region_spent = spawner::spawn_region_from_table(
+                rng,
+                area,
+                map.depth,
+                &spawn_table,
+                allowance,
+                &mut build_data.spawn_list,
+            );
+            spent.threat += region_spent.threat;
+            spent.loot += region_spent.loot;
+        }
+    }
+}
+
+impl VoronoiSpawning {
+    pub fn new() -> Box<VoronoiSpawning> {
+        Box::new(VoronoiSpawning { table: None })
+    }
+
+    pub fn with_table(table: fn(i32) -> RandomTable) -> Box<VoronoiSpawning> {
+        Box::new(VoronoiSpawning { table: Some(table) })
+    }
+}
@@ -1,7 +1,8 @@
 use specs::prelude::*;
 
 use super::{
-    particle_system::ParticleBuilder,Position,CombatStats, DefenseBonus, Equipped, GameLog, MeleePowerBonus, Name, SufferDamage, WantsToMelee,
+    effects::{EffectQueue, EffectType, Targets},
+    CombatStats, DefenseBonus, Equipped, GameLog, MeleePowerBonus, Name, WantsToMelee,
 };
 
 pub struct MeleeCombatSystem {}
@@ -13,12 +14,10 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>
+        WriteExpect<'a, EffectQueue>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -28,12 +27,10 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut logs,
             names,
             combat_stats,
-            mut inflict_damage,
             melee_power_bonuses,
             defense_bonuses,
             equipped,
-            mut particle_builder,
-            positions,
+            mut effects,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
@@ -60,10 +57,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
                             defensive_bonus += defense_bonus.defense;
                         }
                     }
-                    let pos = positions.get(wants_melee.target);
-                    if let Some(pos) = pos {
-                        particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
-                    }
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Particle {
+                            glyph: rltk::to_cp437('‼'),
+                            fg: rltk::RGB::named(rltk::ORANGE),
+                            bg: rltk::RGB::named(rltk::BLACK),
+                            lifetime: 200.0,
+                        },
+                        Targets::Single {
+                            target: wants_melee.target,
+                        },
+                    );
 
                     let damage = i32::max(
                         0,
@@ -80,7 +85,13 @@ impl<'a> System<'a> for MeleeCombatSystem {
                             "{} hits {}, for {} hp.",
                             &name.name, &target_name.name, damage
                         ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Damage { amount: damage },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
                     }
                 }
             }
@@ -0,0 +1,79 @@
+use specs::prelude::*;
+
+use super::{Item, LastSeen, Map, Monster, Position, Renderable};
+
+/// Remembered monsters and items are drawn at this fraction of their greyed colour.
+pub const MEMORY_BRIGHTNESS: f32 = 0.6;
+
+/// Whether the entity is standing somewhere the player can currently see.
+pub fn is_visible(map: &Map, position: Option<&Position>) -> bool {
+    position.is_some_and(|pos| map.visible_tiles[map.xy_idxi32(pos.x, pos.y)])
+}
+
+/// Ages every memory by a turn, then remembers where each visible monster and item is. A
+/// memory is forgotten when its tile comes back into view without the entity on it, or as soon
+/// as the tile is in view if the entity is gone from the level altogether.
+pub struct MemorySystem {}
+
+impl<'a> System<'a> for MemorySystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Renderable>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Item>,
+        WriteStorage<'a, LastSeen>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, map, positions, renderables, monsters, items, mut last_seen) = data;
+
+        for memory in (&mut last_seen).join() {
+            memory.turns_ago += 1;
+        }
+
+        for (entity, pos, render) in (&entities, &positions, &renderables).join() {
+            if !monsters.contains(entity) && !items.contains(entity) {
+                continue;
+            }
+            if map.visible_tiles[map.xy_idxi32(pos.x, pos.y)] {
+                last_seen
+                    .insert(
+                        entity,
+                        LastSeen {
+                            x: pos.x,
+                            y: pos.y,
+                            depth: map.depth,
+                            glyph: render.glyph,
+                            fg: render.fg,
+                            turns_ago: 0,
+                            tile_out_of_view: false,
+                        },
+                    )
+                    .expect("Unable to insert memory");
+            }
+        }
+
+        let mut forgotten: Vec<Entity> = Vec::new();
+        for (entity, memory) in (&entities, &mut last_seen).join() {
+            if memory.depth != map.depth {
+                continue;
+            }
+            let position = positions.get(entity);
+            if memory.turns_ago == 0 || is_visible(&map, position) {
+                continue;
+            }
+            let idx = map.xy_idxi32(memory.x, memory.y);
+            if !map.visible_tiles[idx] {
+                memory.tile_out_of_view = true;
+            } else if memory.tile_out_of_view || position.is_none() {
+                forgotten.push(entity);
+            }
+        }
+        for entity in forgotten.iter() {
+            last_seen.remove(*entity);
+        }
+    }
+}
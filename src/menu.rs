@@ -1,6 +1,6 @@
 use rltk::{Rltk, VirtualKeyCode, RGB};
 
-use super::{gui::MainMenuResult, gui::MainMenuSelection, RunState, State};
+use super::{difficulty::Difficulty, gui::MainMenuResult, gui::MainMenuSelection, RunState, State};
 pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
     let save_exists = super::saveload_system::does_save_exist();
     let runstate = gs.ecs.fetch::<RunState>();
@@ -22,10 +22,17 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
             }
         }
 
+        let difficulty = format!("Difficulty: {}", gs.ecs.fetch::<Difficulty>().name());
+        if selection == MainMenuSelection::Difficulty {
+            ctx.print_color_centered(26, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), &difficulty);
+        } else {
+            ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &difficulty);
+        }
+
         if selection == MainMenuSelection::Quit {
-            ctx.print_color_centered(26, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Quit");
+            ctx.print_color_centered(27, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Quit");
         } else {
-            ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
+            ctx.print_color_centered(27, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
         }
 
         match ctx.key {
@@ -38,7 +45,8 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
                         match selection {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                            MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame
+                            MainMenuSelection::Difficulty => newselection = MainMenuSelection::LoadGame,
+                            MainMenuSelection::Quit => newselection = MainMenuSelection::Difficulty
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists {
                             newselection = MainMenuSelection::NewGame;
@@ -49,11 +57,12 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
                         let mut newselection;
                         match selection {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Difficulty,
+                            MainMenuSelection::Difficulty => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists {
-                            newselection = MainMenuSelection::Quit;
+                            newselection = MainMenuSelection::Difficulty;
                         }
                         return MainMenuResult::NoSelection{ selected: newselection }
                     }
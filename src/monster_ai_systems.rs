@@ -1,4 +1,4 @@
-use super::{Map, Monster, Position, RunState, Viewshed, WantsToMelee, Confusion};
+use super::{Map, Monster, Position, RunState, Viewshed, WantsToMelee, Confusion, SlowedByTerrain, TileType, tile_walkable};
 use rltk::{Point};
 use specs::prelude::*;
 
@@ -16,11 +16,12 @@ impl<'a> System<'a> for MonsterAI {
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>
+        WriteStorage<'a, Confusion>,
+        WriteStorage<'a, SlowedByTerrain>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut map, player_pos, player_entity, runstate, entities, mut viewshed, monster, mut position, mut wants_to_melee, mut confused) = data;
+        let (mut map, player_pos, player_entity, runstate, entities, mut viewshed, monster, mut position, mut wants_to_melee, mut confused, mut slowed) = data;
 
         if *runstate != RunState::MonsterTurn { return; }
 
@@ -36,6 +37,11 @@ impl<'a> System<'a> for MonsterAI {
                 can_act = false;
             }
 
+            // Still climbing over rubble
+            if slowed.remove(entity).is_some() {
+                can_act = false;
+            }
+
             if can_act {
                 let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
                 if distance < 1.5 {
@@ -48,7 +54,7 @@ impl<'a> System<'a> for MonsterAI {
                         map.xy_idxi32(player_pos.x, player_pos.y),
                         &*map
                     );
-                    if path.success && path.steps.len()>1 {
+                    if path.success && path.steps.len()>1 && tile_walkable(map.tiles[path.steps[1]]) {
                         let mut idx = map.xy_idxi32(pos.x, pos.y);
                         map.blocked[idx] = false;
                         pos.x = path.steps[1] as i32 % map.width;
@@ -56,6 +62,9 @@ impl<'a> System<'a> for MonsterAI {
                         idx = map.xy_idxi32(pos.x, pos.y);
                         map.blocked[idx] = true;
                         viewshed.dirty = true;
+                        if map.tiles[idx] == TileType::Rubble {
+                            slowed.insert(entity, SlowedByTerrain{}).expect("Unable to insert slow");
+                        }
                     }
                 }
             }
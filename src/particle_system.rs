@@ -1,5 +1,8 @@
 use specs::prelude::*;
-use super::{ Rltk, ParticleLifetime, Renderable, Position};
+use super::{ Rltk, ParticleLifetime, Renderable, Position, LightSource};
+
+/// Particles glow faintly in their own colour.
+const PARTICLE_LIGHT_RANGE: i32 = 2;
 use rltk::RGB;
 
 struct ParticleRequest {
@@ -8,7 +11,8 @@ struct ParticleRequest {
     fg: RGB,
     bg: RGB,
     glyph: rltk::FontCharType,
-    lifetime: f32
+    lifetime: f32,
+    glows: bool
 }
 
 pub struct ParticleBuilder {
@@ -24,7 +28,17 @@ impl ParticleBuilder {
     pub fn request(&mut self, x:i32, y:i32, fg: RGB, bg:RGB, glyph: rltk::FontCharType, lifetime: f32) {
         self.requests.push(
             ParticleRequest{
-                x, y, fg, bg, glyph, lifetime
+                x, y, fg, bg, glyph, lifetime, glows: true
+            }
+        );
+    }
+
+    /// Like `request`, but the particle doesn't light up its surroundings. Used for effects
+    /// that cover lots of tiles the player shouldn't suddenly be able to see.
+    pub fn request_unlit(&mut self, x:i32, y:i32, fg: RGB, bg:RGB, glyph: rltk::FontCharType, lifetime: f32) {
+        self.requests.push(
+            ParticleRequest{
+                x, y, fg, bg, glyph, lifetime, glows: false
             }
         );
     }
@@ -51,24 +65,26 @@ pub fn cull_dead_particles(ecs : &mut World, ctx : &Rltk) {
 pub struct ParticleSpawnSystem {}
 
 impl<'a> System<'a> for ParticleSpawnSystem {
-    #[allow(clippy::type_complexity)]
+    // Particles are created lazily so this system doesn't touch any storages and can run
+    // alongside the others.
     type SystemData = (
         Entities<'a>,
-        WriteStorage<'a, Position>,
-        WriteStorage<'a, Renderable>,
-        WriteStorage<'a, ParticleLifetime>,
+        Read<'a, LazyUpdate>,
         WriteExpect<'a, ParticleBuilder>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (entities, mut positions, mut renderables, mut particles, mut particle_builder) = data;
+        let (entities, lazy, mut particle_builder) = data;
         for new_particle in particle_builder.requests.iter() {
             let p = entities.create();
-            positions.insert(p, Position{ x: new_particle.x, y: new_particle.y }).expect("Unable to inser position");
-            renderables.insert(p, Renderable{ fg: new_particle.fg, bg: new_particle.bg, glyph: new_particle.glyph, render_order: 0 }).expect("Unable to insert renderable");
-            particles.insert(p, ParticleLifetime{ lifetime_ms: new_particle.lifetime }).expect("Unable to insert lifetime");
+            lazy.insert(p, Position{ x: new_particle.x, y: new_particle.y });
+            lazy.insert(p, Renderable{ fg: new_particle.fg, bg: new_particle.bg, glyph: new_particle.glyph, render_order: 0 });
+            lazy.insert(p, ParticleLifetime{ lifetime_ms: new_particle.lifetime });
+            if new_particle.glows {
+                lazy.insert(p, LightSource{ color: new_particle.fg, range: PARTICLE_LIGHT_RANGE });
+            }
         }
 
         particle_builder.requests.clear();
     }
-}
\ No newline at end of file
+}
@@ -3,47 +3,72 @@ use std::cmp::{max, min};
 use rltk::{Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
+use super::wizard::wizard_input;
 use super::{
-    CombatStats, GameLog, Item, KnownSpells, MagicStats, Map, Player, Position, RunState, Spell,
-    State, TileType, Viewshed, WantsToMelee, WantsToPickupItem,
+    tile_walkable, CombatStats, GameLog, Item, KnownSpells, MagicStats, Map, Player, Position,
+    RunState, SlowedByTerrain, Spell, State, TileType, Viewshed, WantsToMelee, WantsToPickupItem,
 };
 
-pub(crate) fn player_input(gs: &mut State, ctx: &mut Rltk, world_size: Position) -> RunState {
+pub(crate) fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    // Wizard mode cheats take priority over the regular bindings
+    if let Some(key) = ctx.key {
+        if let Some(newrunstate) = wizard_input(&mut gs.ecs, key) {
+            return newrunstate;
+        }
+    }
+
+    // Still climbing over rubble: like a monster, the player loses this turn
+    if ctx.key.is_some() {
+        let player_entity = *gs.ecs.fetch::<Entity>();
+        if gs
+            .ecs
+            .write_storage::<SlowedByTerrain>()
+            .remove(player_entity)
+            .is_some()
+        {
+            gs.ecs
+                .fetch_mut::<GameLog>()
+                .entries
+                .push("You clamber over the rubble.".to_string());
+            return RunState::PlayerTurn;
+        }
+    }
+
     //player movement
     match ctx.key {
         None => return RunState::AwaitingInput,
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, &mut gs.ecs, world_size)
+                try_move_player(-1, 0, &mut gs.ecs)
             }
 
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, &mut gs.ecs, world_size)
+                try_move_player(1, 0, &mut gs.ecs)
             }
 
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, &mut gs.ecs, world_size)
+                try_move_player(0, -1, &mut gs.ecs)
             }
 
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, &mut gs.ecs, world_size)
+                try_move_player(0, 1, &mut gs.ecs)
             }
 
             // Diagonals
             VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => {
-                try_move_player(1, -1, &mut gs.ecs, world_size)
+                try_move_player(1, -1, &mut gs.ecs)
             }
 
             VirtualKeyCode::Numpad7 | VirtualKeyCode::U => {
-                try_move_player(-1, -1, &mut gs.ecs, world_size)
+                try_move_player(-1, -1, &mut gs.ecs)
             }
 
             VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                try_move_player(1, 1, &mut gs.ecs, world_size)
+                try_move_player(1, 1, &mut gs.ecs)
             }
 
             VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                try_move_player(-1, 1, &mut gs.ecs, world_size)
+                try_move_player(-1, 1, &mut gs.ecs)
             }
 
             VirtualKeyCode::Key1 => {
@@ -86,6 +111,11 @@ pub(crate) fn player_input(gs: &mut State, ctx: &mut Rltk, world_size: Position)
                     return RunState::NextLevel;
                 }
             }
+            VirtualKeyCode::Comma => {
+                if try_previous_level(&mut gs.ecs) {
+                    return RunState::PreviousLevel;
+                }
+            }
             _ => return RunState::AwaitingInput,
         },
     }
@@ -116,6 +146,21 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     }
 }
 
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idxi32(player_pos.x, player_pos.y);
+    if map.tiles[player_idx] == TileType::UpStairs {
+        true
+    } else {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog
+            .entries
+            .push("There is no way up from here.".to_string());
+        false
+    }
+}
+
 fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
@@ -150,7 +195,7 @@ fn get_item(ecs: &mut World) {
     }
 }
 
-fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World, world_size: Position) {
+fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -160,6 +205,8 @@ fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World, world_size: Posi
 
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    let mut slowed = ecs.write_storage::<SlowedByTerrain>();
 
     for (entity, _player, pos, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewsheds).join()
@@ -180,14 +227,32 @@ fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World, world_size: Posi
                 return;
             }
         }
+        if !tile_walkable(map.tiles[destination_idx]) {
+            let message = match map.tiles[destination_idx] {
+                TileType::DeepWater => Some("The water is too deep to wade through."),
+                TileType::Lava => Some("You would burn alive in the lava."),
+                TileType::Chasm => Some("You stop at the edge of the chasm."),
+                _ => None,
+            };
+            if let Some(message) = message {
+                gamelog.entries.push(message.to_string());
+            }
+            return;
+        }
         if !map.blocked[destination_idx] {
-            pos.x = min(world_size.x - 1, max(0, pos.x + delta_x));
-            pos.y = min(world_size.y - 1, max(0, pos.y + delta_y));
+            pos.x = min(map.width - 1, max(0, pos.x + delta_x));
+            pos.y = min(map.height - 1, max(0, pos.y + delta_y));
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
             ppos.x = pos.x;
             ppos.y = pos.y;
+
+            if map.tiles[destination_idx] == TileType::Rubble {
+                slowed
+                    .insert(entity, SlowedByTerrain {})
+                    .expect("Unable to insert slow");
+            }
         }
     }
 }
@@ -17,42 +17,93 @@ impl RandomEntry {
 #[derive(Default)]
 pub struct RandomTable {
     entries: Vec<RandomEntry>,
-    total_weight: i32,
 }
 
 impl RandomTable {
     pub fn new() -> RandomTable {
         RandomTable {
             entries: Vec::new(),
-            total_weight: 0,
         }
     }
 
     pub fn add<S: ToString>(mut self, name: S, weight: i32) -> RandomTable {
         if weight > 0 {
-            self.total_weight += weight;
             self.entries
                 .push(RandomEntry::new(name.to_string(), weight));
         }
         self
     }
 
+    /// Rolls any entry, by weight. Returns "None" only if the table is empty.
     pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
-        if self.total_weight == 0 {
-            return "None".to_string();
+        self.roll_where(rng, |_name| true)
+            .unwrap_or_else(|| "None".to_string())
+    }
+
+    /// Rolls among only the entries whose name passes `allowed`, keeping their relative
+    /// weights. Returns None if no entry is allowed.
+    pub fn roll_where<F: Fn(&str) -> bool>(
+        &self,
+        rng: &mut RandomNumberGenerator,
+        allowed: F,
+    ) -> Option<String> {
+        let candidates: Vec<&RandomEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| allowed(&entry.name))
+            .collect();
+        let total_weight: i32 = candidates.iter().map(|entry| entry.weight).sum();
+        if total_weight == 0 {
+            return None;
         }
-        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
-        let mut index: usize = 0;
 
-        while roll > 0 {
-            if roll < self.entries[index].weight {
-                return self.entries[index].name.clone();
+        let mut roll = rng.roll_dice(1, total_weight) - 1;
+        for entry in candidates {
+            if roll < entry.weight {
+                return Some(entry.name.clone());
             }
-
-            roll -= self.entries[index].weight;
-            index += 1;
+            roll -= entry.weight;
         }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(rolls: &[String], name: &str) -> usize {
+        rolls.iter().filter(|roll| *roll == name).count()
+    }
+
+    #[test]
+    fn filtered_rolls_keep_relative_weights() {
+        let table = RandomTable::new().add("A", 1).add("B", 3).add("C", 5);
+        let mut rng = RandomNumberGenerator::seeded(3);
+        let rolls: Vec<String> = (0..4000)
+            .map(|_| table.roll_where(&mut rng, |name| name != "C").unwrap())
+            .collect();
+        assert_eq!(count(&rolls, "C"), 0);
+        let (a, b) = (count(&rolls, "A") as f32, count(&rolls, "B") as f32);
+        assert!(b / a > 2.5 && b / a < 3.5, "A {} B {}", a, b);
+    }
+
+    #[test]
+    fn first_entry_can_be_rolled() {
+        let table = RandomTable::new().add("A", 1).add("B", 1);
+        let mut rng = RandomNumberGenerator::seeded(5);
+        let rolls: Vec<String> = (0..200).map(|_| table.roll(&mut rng)).collect();
+        assert!(count(&rolls, "A") > 0);
+        assert_eq!(count(&rolls, "None"), 0);
+    }
+
+    #[test]
+    fn nothing_to_roll() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        assert_eq!(RandomTable::new().roll(&mut rng), "None");
+        assert_eq!(RandomTable::new().roll_where(&mut rng, |_name| true), None);
 
-        "None".to_string()
+        let table = RandomTable::new().add("A", 2).add("B", 0);
+        assert_eq!(table.roll_where(&mut rng, |name| name == "B"), None);
     }
 }
@@ -1,5 +1,7 @@
 use super::components::*;
-use super::MAP_COUNT;
+use super::difficulty::Difficulty;
+use super::dungeon::DungeonMaster;
+use super::wizard::WizardMode;
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{
@@ -45,9 +47,17 @@ pub fn does_save_exist() -> bool {
 pub fn save_game(ecs: &mut World) {
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let wizardcopy = WizardMode::clone(&ecs.fetch::<WizardMode>());
+    let dungeoncopy = DungeonMaster::clone(&ecs.fetch::<DungeonMaster>());
+    let difficulty = *ecs.fetch::<Difficulty>();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            wizard: wizardcopy,
+            dungeon_master: dungeoncopy,
+            difficulty,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -65,6 +75,7 @@ pub fn save_game(ecs: &mut World) {
             serializer,
             data,
             Position,
+            OtherLevelPosition,
             Renderable,
             Player,
             Viewshed,
@@ -72,7 +83,6 @@ pub fn save_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
@@ -82,6 +92,7 @@ pub fn save_game(ecs: &mut World) {
             AreaOfEffect,
             Confusion,
             ProvidesHealing,
+            ProvidesMana,
             InBackpack,
             WantsToPickupItem,
             WantsToUseItem,
@@ -89,6 +100,14 @@ pub fn save_game(ecs: &mut World) {
             SerializationHelper,
             Equippable,
             DestroysWalls,
+            SlowedByTerrain,
+            LightSource,
+            MagicMapper,
+            DetectsMonsters,
+            DetectsItems,
+            SeesMonsters,
+            SeesItems,
+            LastSeen,
             CanTargetAnything,
             Equipped,
             MeleePowerBonus,
@@ -130,6 +149,7 @@ pub fn load_game(ecs: &mut World) {
             de,
             d,
             Position,
+            OtherLevelPosition,
             Renderable,
             Player,
             Viewshed,
@@ -137,7 +157,6 @@ pub fn load_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
@@ -147,6 +166,7 @@ pub fn load_game(ecs: &mut World) {
             AreaOfEffect,
             Confusion,
             ProvidesHealing,
+            ProvidesMana,
             InBackpack,
             WantsToPickupItem,
             WantsToUseItem,
@@ -154,6 +174,14 @@ pub fn load_game(ecs: &mut World) {
             SerializationHelper,
             Equippable,
             DestroysWalls,
+            SlowedByTerrain,
+            LightSource,
+            MagicMapper,
+            DetectsMonsters,
+            DetectsItems,
+            SeesMonsters,
+            SeesItems,
+            LastSeen,
             CanTargetAnything,
             Equipped,
             MeleePowerBonus,
@@ -173,7 +201,10 @@ pub fn load_game(ecs: &mut World) {
         for (e, h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
-            worldmap.tile_content = vec![Vec::new(); MAP_COUNT];
+            worldmap.tile_content = vec![Vec::new(); worldmap.tile_count()];
+            *ecs.write_resource::<WizardMode>() = h.wizard.clone();
+            *ecs.write_resource::<DungeonMaster>() = h.dungeon_master.clone();
+            *ecs.write_resource::<Difficulty>() = h.difficulty;
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
@@ -1,18 +1,16 @@
-use std::collections::HashMap;
-
 use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 use super::spells::fireball;
 use super::{
     random_table::RandomTable, AreaOfEffect, BlocksTile, CombatStats, Confusion, Consumable,
-    DestroysWalls, InflictsDamage, Item, KnownSpells, MagicStats, Monster, Name, Player, Position,
-    ProvidesHealing, Ranged, Rect, Renderable, SerializeMe, Viewshed,KnownSpell
+    DestroysWalls, DetectsItems, DetectsMonsters, InflictsDamage, Item, KnownSpells, MagicMapper, LightSource, MagicStats, Monster, Name,
+    Player, Position, ProvidesHealing, ProvidesMana, Ranged, Renderable, SerializeMe, Viewshed,KnownSpell
 };
 use super::{
     DefenseBonus, MeleePowerBonus, {EquipmentSlot, Equippable},
 };
-use super::{MAX_MONSTERS, WIDTH};
+use super::{difficulty::Difficulty, themes::LevelTheme, Map};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use std::borrow::BorrowMut;
 
@@ -59,59 +57,263 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .build()
 }
 
-pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+/// Whether a spawn is paid for out of a level's threat budget or its loot budget.
+#[derive(PartialEq, Copy, Clone)]
+pub enum SpawnKind {
+    Monster,
+    Item,
+}
 
-    // Scope to keep the borrow checker happy
-    {
-        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3;
-
-        for _i in 0..num_spawns {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 20 {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * WIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                } else {
-                    tries += 1;
-                }
+/// What a spawn table entry costs and how deep it may turn up. Entries outside their depth
+/// range, or costing more than is left to spend, are left out of the roll.
+pub struct SpawnInfo {
+    pub name: &'static str,
+    pub kind: SpawnKind,
+    pub cost: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
+
+const fn spawn_info(
+    name: &'static str,
+    kind: SpawnKind,
+    cost: i32,
+    min_depth: i32,
+    max_depth: i32,
+) -> SpawnInfo {
+    SpawnInfo {
+        name,
+        kind,
+        cost,
+        min_depth,
+        max_depth,
+    }
+}
+
+/// Every entry the spawn tables can roll. Sconces are placed by `SconceSpawner` and cost
+/// nothing, so they aren't listed.
+const SPAWN_INFO: [SpawnInfo; 17] = [
+    spawn_info("Goblin", SpawnKind::Monster, 1, 1, 10),
+    spawn_info("Myconid", SpawnKind::Monster, 1, 2, i32::MAX),
+    spawn_info("Orc", SpawnKind::Monster, 2, 2, i32::MAX),
+    spawn_info("Bog Lurker", SpawnKind::Monster, 2, 3, i32::MAX),
+    spawn_info("Skeleton", SpawnKind::Monster, 3, 4, i32::MAX),
+    spawn_info("Animated Tome", SpawnKind::Monster, 3, 6, i32::MAX),
+    spawn_info("Health Potion", SpawnKind::Item, 1, 1, i32::MAX),
+    spawn_info("Mana Potion", SpawnKind::Item, 1, 1, i32::MAX),
+    spawn_info("Magic Missile Scroll", SpawnKind::Item, 1, 1, i32::MAX),
+    spawn_info("Item Detection Scroll", SpawnKind::Item, 1, 1, i32::MAX),
+    spawn_info("Confusion Scroll", SpawnKind::Item, 2, 1, i32::MAX),
+    spawn_info("Magic Mapping Scroll", SpawnKind::Item, 2, 1, i32::MAX),
+    spawn_info("Telepathy Scroll", SpawnKind::Item, 2, 1, i32::MAX),
+    spawn_info("Fireball Scroll", SpawnKind::Item, 3, 2, i32::MAX),
+    spawn_info("Dagger", SpawnKind::Item, 2, 1, 8),
+    spawn_info("Shield", SpawnKind::Item, 2, 1, i32::MAX),
+    spawn_info("Torch", SpawnKind::Item, 1, 2, i32::MAX),
+];
+
+pub fn get_spawn_info(name: &str) -> Option<&'static SpawnInfo> {
+    SPAWN_INFO.iter().find(|info| info.name == name)
+}
+
+/// Threat points a level gets on the first depth, and how many more every level below adds.
+const BASE_THREAT: f32 = 6.0;
+const THREAT_PER_DEPTH: f32 = 3.0;
+/// The same for loot, which grows much more slowly than the threat.
+const BASE_LOOT: f32 = 6.0;
+const LOOT_PER_DEPTH: f32 = 1.0;
+
+/// Points to spend on monsters (`threat`) and items (`loot`), for a whole level or the part
+/// of it left to a spawn region.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct EncounterBudget {
+    pub threat: i32,
+    pub loot: i32,
+}
+
+impl EncounterBudget {
+    pub fn for_level(depth: i32, difficulty: Difficulty) -> EncounterBudget {
+        let levels_down = (depth - 1).max(0) as f32;
+        let threat =
+            (BASE_THREAT + THREAT_PER_DEPTH * levels_down) * difficulty.threat_multiplier();
+        let loot = (BASE_LOOT + LOOT_PER_DEPTH * levels_down) * difficulty.loot_multiplier();
+        EncounterBudget {
+            threat: threat.round() as i32,
+            loot: loot.round() as i32,
+        }
+    }
+
+    /// The part of the budget belonging to the first `covered` of `total` tiles, so that
+    /// regions handed out one after another split it in proportion to their size.
+    pub fn portion(&self, covered: usize, total: usize) -> EncounterBudget {
+        if total == 0 {
+            return EncounterBudget::default();
+        }
+        let share = |points: i32| (points as f32 * covered as f32 / total as f32).round() as i32;
+        EncounterBudget {
+            threat: share(self.threat),
+            loot: share(self.loot),
+        }
+    }
+
+    /// What the spawns in `spawn_list` cost between them. Spawns without spawn info are free.
+    pub fn spent_on(spawn_list: &[(usize, String)]) -> EncounterBudget {
+        let mut spent = EncounterBudget::default();
+        for info in spawn_list.iter().filter_map(|(_idx, name)| get_spawn_info(name)) {
+            match info.kind {
+                SpawnKind::Monster => spent.threat += info.cost,
+                SpawnKind::Item => spent.loot += info.cost,
             }
         }
+        spent
     }
 
-    // Actually spawn the monsters
-    for spawn in spawn_points.iter() {
-        let x = (*spawn.0 % WIDTH) as i32;
-        let y = (*spawn.0 / WIDTH) as i32;
-
-        match spawn.1.as_ref() {
-            "Goblin" => goblin(ecs, x, y),
-            "Orc" => orc(ecs, x, y),
-            "Health Potion" => health_potion(ecs, x, y),
-            "Fireball Scroll" => fireball_scroll(ecs, x, y),
-            "Confusion Scroll" => confusion_scroll(ecs, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-            "Dagger" => dagger(ecs, x, y),
-            "Shield" => shield(ecs, x, y),
-            _ => {}
+    /// What is left of this budget once `spent` has been paid out of it.
+    pub fn minus(&self, spent: &EncounterBudget) -> EncounterBudget {
+        EncounterBudget {
+            threat: self.threat - spent.threat,
+            loot: self.loot - spent.loot,
         }
     }
+
+    /// Whether `info` may spawn at `depth` and there are enough points left of its kind.
+    fn affords(&self, info: &SpawnInfo, depth: i32) -> bool {
+        let points = match info.kind {
+            SpawnKind::Monster => self.threat,
+            SpawnKind::Item => self.loot,
+        };
+        depth >= info.min_depth && depth <= info.max_depth && points >= info.cost
+    }
+
+    fn spend(&mut self, info: &SpawnInfo) {
+        match info.kind {
+            SpawnKind::Monster => self.threat -= info.cost,
+            SpawnKind::Item => self.loot -= info.cost,
+        }
+    }
+}
+
+/// Spends a region's encounter budget on spawns rolled from `spawn_table`, each on a different
+/// tile of `area`. Only entries that fit the depth and the points left are rolled, so the
+/// region stops once nothing more can be afforded. Returns what was spent.
+pub fn spawn_region_from_table(
+    rng: &mut RandomNumberGenerator,
+    area: &[usize],
+    map_depth: i32,
+    spawn_table: &RandomTable,
+    budget: EncounterBudget,
+    spawn_list: &mut Vec<(usize, String)>,
+) -> EncounterBudget {
+    let mut areas: Vec<usize> = Vec::from(area);
+    let mut remaining = budget;
+
+    while !areas.is_empty() {
+        let roll = spawn_table.roll_where(rng, |name| {
+            get_spawn_info(name).is_some_and(|info| remaining.affords(info, map_depth))
+        });
+        let name = match roll {
+            Some(name) => name,
+            None => break,
+        };
+        let info = get_spawn_info(&name).expect("Rolled a spawn without spawn info");
+        remaining.spend(info);
+        let map_idx = areas.remove(rng.range(0, areas.len() as i32) as usize);
+        spawn_list.push((map_idx, name));
+    }
+    budget.minus(&remaining)
 }
 
-fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
+/// Spawns one entry of a builder's spawn list.
+pub fn spawn_entity(ecs: &mut World, map: &Map, spawn: &(usize, String)) {
+    let (x, y) = map.idx_to_xy(spawn.0);
+    spawn_named_entity(ecs, &spawn.1, x, y);
 }
-fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
+
+/// Every name `spawn_named_entity` knows how to build.
+pub const SPAWNABLE_NAMES: [&str; 18] = [
+    "Goblin",
+    "Orc",
+    "Skeleton",
+    "Myconid",
+    "Bog Lurker",
+    "Animated Tome",
+    "Health Potion",
+    "Mana Potion",
+    "Fireball Scroll",
+    "Confusion Scroll",
+    "Magic Missile Scroll",
+    "Magic Mapping Scroll",
+    "Telepathy Scroll",
+    "Item Detection Scroll",
+    "Dagger",
+    "Shield",
+    "Torch",
+    "Sconce",
+];
+
+/// Spawns the spawn table entry called `name` at the given position. Returns None if no such
+/// entry exists.
+pub fn spawn_named_entity(ecs: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+    match name {
+        "Goblin" => Some(goblin(ecs, x, y)),
+        "Orc" => Some(orc(ecs, x, y)),
+        "Skeleton" => Some(skeleton(ecs, x, y)),
+        "Myconid" => Some(myconid(ecs, x, y)),
+        "Bog Lurker" => Some(bog_lurker(ecs, x, y)),
+        "Animated Tome" => Some(animated_tome(ecs, x, y)),
+        "Health Potion" => Some(health_potion(ecs, x, y)),
+        "Mana Potion" => Some(mana_potion(ecs, x, y)),
+        "Fireball Scroll" => Some(fireball_scroll(ecs, x, y)),
+        "Confusion Scroll" => Some(confusion_scroll(ecs, x, y)),
+        "Magic Mapping Scroll" => Some(magic_mapping_scroll(ecs, x, y)),
+        "Telepathy Scroll" => Some(telepathy_scroll(ecs, x, y)),
+        "Item Detection Scroll" => Some(item_detection_scroll(ecs, x, y)),
+        "Magic Missile Scroll" => Some(magic_missile_scroll(ecs, x, y)),
+        "Dagger" => Some(dagger(ecs, x, y)),
+        "Shield" => Some(shield(ecs, x, y)),
+        "Torch" => Some(torch(ecs, x, y)),
+        "Sconce" => Some(sconce(ecs, x, y)),
+        _ => None,
+    }
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
+// Tougher monsters cost more, see `SPAWN_INFO`.
+fn orc(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('o'), "Orc", stats(16, 1, 4))
+}
+fn goblin(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin", stats(10, 0, 3))
+}
+fn skeleton(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('s'), "Skeleton", stats(22, 2, 5))
+}
+fn myconid(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('m'), "Myconid", stats(12, 0, 3))
+}
+fn bog_lurker(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('l'), "Bog Lurker", stats(18, 1, 4))
+}
+fn animated_tome(ecs: &mut World, x: i32, y: i32) -> Entity {
+    monster(ecs, x, y, rltk::to_cp437('b'), "Animated Tome", stats(16, 1, 6))
+}
+
+fn stats(max_hp: i32, defense: i32, power: i32) -> CombatStats {
+    CombatStats {
+        max_hp,
+        hp: max_hp,
+        defense,
+        power,
+    }
+}
+
+fn monster<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    name: S,
+    stats: CombatStats,
+) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -130,17 +332,12 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharTy
             name: name.to_string(),
         })
         .with(BlocksTile {})
-        .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
-        })
+        .with(stats)
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn health_potion(ecs: &mut World, x: i32, y: i32) {
+fn health_potion(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -156,10 +353,29 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
         .with(Consumable {})
         .with(ProvidesHealing { healing_amount: 8 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
+fn mana_potion(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Mana Potion".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(ProvidesMana { mana_amount: 5 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn fireball_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -176,12 +392,12 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Ranged { range: 6 })
         .with(InflictsDamage { damage: 20 })
         .with(AreaOfEffect { radius: 3 })
-        .with(DestroysWalls {})
+        .with(DestroysWalls { damage: 20 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -198,10 +414,10 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Ranged { range: 6 })
         .with(InflictsDamage { damage: 8 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+fn confusion_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -218,10 +434,67 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Ranged { range: 6 })
         .with(Confusion { duration: 4 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
+}
+
+fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Magic Mapping Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(MagicMapper { radius: None })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn telepathy_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Telepathy Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(DetectsMonsters { duration: 30 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn item_detection_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Item Detection Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(DetectsItems { duration: 30 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
 }
 
-fn dagger(ecs: &mut World, x: i32, y: i32) {
+fn dagger(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -239,10 +512,10 @@ fn dagger(ecs: &mut World, x: i32, y: i32) {
             slot: EquipmentSlot::Melee,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn shield(ecs: &mut World, x: i32, y: i32) {
+fn shield(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -260,10 +533,66 @@ fn shield(ecs: &mut World, x: i32, y: i32) {
         })
         .with(DefenseBonus { defense: 2 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn room_table(depth: i32) -> RandomTable {
+fn torch(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('⌠'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Torch".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Light,
+        })
+        .with(LightSource {
+            color: RGB::from_f32(1.0, 0.8, 0.5),
+            range: 6,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+/// A fixed light hung by the builders; not something the player can take.
+fn sconce(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('☼'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Sconce".to_string(),
+        })
+        .with(LightSource {
+            color: RGB::from_f32(1.0, 0.9, 0.6),
+            range: 5,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+/// The spawn table for a level of the given theme and depth.
+pub fn themed_table(theme: LevelTheme, depth: i32) -> RandomTable {
+    match theme {
+        LevelTheme::Dungeon => room_table(depth),
+        LevelTheme::Crypt => crypt_table(depth),
+        LevelTheme::FungalCave => fungal_cave_table(depth),
+        LevelTheme::FloodedRuins => flooded_ruins_table(depth),
+        LevelTheme::ArcaneLibrary => arcane_library_table(depth),
+    }
+}
+
+pub fn room_table(depth: i32) -> RandomTable {
     RandomTable::new()
         .add("Goblin", 10)
         .add("Orc", 1 + depth)
@@ -271,6 +600,137 @@ fn room_table(depth: i32) -> RandomTable {
         .add("Fireball Scroll", 2 + depth)
         .add("Confusion Scroll", 2 + depth)
         .add("Magic Missile Scroll", 4)
+        .add("Magic Mapping Scroll", 2)
+        .add("Telepathy Scroll", 1 + depth / 2)
+        .add("Item Detection Scroll", 2)
         .add("Dagger", 3)
         .add("Shield", 3)
+        .add("Torch", depth - 1)
+}
+
+/// Mazes are cramped, so fewer orcs and more potions to keep the player going.
+pub fn maze_table(depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Goblin", 10)
+        .add("Orc", depth)
+        .add("Health Potion", 8)
+        .add("Mana Potion", 8)
+        .add("Confusion Scroll", 3)
+        .add("Magic Missile Scroll", 4)
+        .add("Magic Mapping Scroll", 4)
+        .add("Telepathy Scroll", 2)
+        .add("Torch", depth)
+}
+
+/// The dead walk here, and torches are needed to see them coming.
+pub fn crypt_table(depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Skeleton", 10)
+        .add("Goblin", 3)
+        .add("Orc", depth)
+        .add("Health Potion", 7)
+        .add("Confusion Scroll", 3)
+        .add("Magic Missile Scroll", 3)
+        .add("Telepathy Scroll", 3)
+        .add("Shield", 3)
+        .add("Torch", depth)
+}
+
+/// Fire clears fungus nicely.
+pub fn fungal_cave_table(depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Myconid", 10)
+        .add("Goblin", 5)
+        .add("Orc", depth / 2)
+        .add("Health Potion", 8)
+        .add("Mana Potion", 4)
+        .add("Fireball Scroll", 3 + depth)
+        .add("Item Detection Scroll", 2)
+        .add("Dagger", 3)
+}
+
+pub fn flooded_ruins_table(depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Bog Lurker", 8)
+        .add("Goblin", 5)
+        .add("Orc", 1 + depth)
+        .add("Health Potion", 7)
+        .add("Confusion Scroll", 2 + depth)
+        .add("Magic Mapping Scroll", 4)
+        .add("Shield", 3)
+        .add("Torch", depth)
+}
+
+/// Few weapons, but plenty of scrolls and the mana to cast spells.
+pub fn arcane_library_table(depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Animated Tome", 10)
+        .add("Goblin", 3)
+        .add("Health Potion", 5)
+        .add("Mana Potion", 8)
+        .add("Fireball Scroll", 2 + depth)
+        .add("Confusion Scroll", 2 + depth)
+        .add("Magic Missile Scroll", 6)
+        .add("Magic Mapping Scroll", 3)
+        .add("Telepathy Scroll", 3)
+        .add("Item Detection Scroll", 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budgets_grow_with_depth() {
+        let first = EncounterBudget::for_level(1, Difficulty::Normal);
+        assert_eq!(first, EncounterBudget { threat: 6, loot: 6 });
+        let deeper = EncounterBudget::for_level(5, Difficulty::Normal);
+        assert_eq!(deeper, EncounterBudget { threat: 18, loot: 10 });
+    }
+
+    #[test]
+    fn difficulty_trades_loot_for_threat() {
+        let easy = EncounterBudget::for_level(5, Difficulty::Easy);
+        let normal = EncounterBudget::for_level(5, Difficulty::Normal);
+        let hard = EncounterBudget::for_level(5, Difficulty::Hard);
+        assert!(easy.threat < normal.threat && normal.threat < hard.threat);
+        assert!(easy.loot > normal.loot && normal.loot > hard.loot);
+    }
+
+    #[test]
+    fn portions_add_up_to_the_whole_budget() {
+        let budget = EncounterBudget { threat: 7, loot: 3 };
+        assert_eq!(budget.portion(0, 90), EncounterBudget::default());
+        assert_eq!(budget.portion(90, 90), budget);
+        assert_eq!(budget.portion(45, 90), EncounterBudget { threat: 4, loot: 2 });
+        assert_eq!(budget.portion(5, 0), EncounterBudget::default());
+    }
+
+    #[test]
+    fn spent_budget_is_subtracted() {
+        let spawns = vec![
+            (0, "Orc".to_string()),
+            (1, "Fireball Scroll".to_string()),
+            (2, "Sconce".to_string()),
+        ];
+        let spent = EncounterBudget::spent_on(&spawns);
+        assert_eq!(spent, EncounterBudget { threat: 2, loot: 3 });
+        let budget = EncounterBudget { threat: 6, loot: 6 };
+        assert_eq!(budget.minus(&spent), EncounterBudget { threat: 4, loot: 3 });
+    }
+
+    #[test]
+    fn regions_only_spawn_what_fits() {
+        let table = RandomTable::new().add("Goblin", 1).add("Orc", 1).add("Fireball Scroll", 1);
+        let area: Vec<usize> = (0..50).collect();
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let mut spawn_list = Vec::new();
+        let budget = EncounterBudget { threat: 5, loot: 2 };
+        let spent = spawn_region_from_table(&mut rng, &area, 1, &table, budget, &mut spawn_list);
+
+        // Orcs are too deep for the first level and a fireball costs more than the loot budget
+        assert_eq!(spent, EncounterBudget { threat: 5, loot: 0 });
+        assert!(spawn_list.iter().all(|(_idx, name)| name == "Goblin"));
+        assert_eq!(spawn_list.len(), 5);
+    }
 }
@@ -19,11 +19,22 @@ pub enum SpellComponent {
 pub fn add_spell_component(ecs: &mut World, spell_entity: &mut Entity, spell_component: SpellComponent) {
 }
 
+/// Every spell `learn_spell` knows how to create.
+pub const SPELL_NAMES: [&str; 1] = ["fireball"];
+
+/// Creates the spell entity for `name` and returns the matching `KnownSpell`, if there is one.
+pub fn learn_spell(ecs: &mut World, name: &str) -> Option<KnownSpell> {
+    match name {
+        "fireball" => Some(fireball(ecs)),
+        _ => None,
+    }
+}
+
 pub fn fireball(ecs: &mut World) -> KnownSpell {
     ecs.create_entity()
         .with(InflictsDamage { damage: 20 })
         .with(AreaOfEffect { radius: 3 })
-        .with(DestroysWalls {})
+        .with(DestroysWalls { damage: 20 })
         .with(Spell {
             name: "fireball".to_string(),
             range: 6,
@@ -0,0 +1,100 @@
+use rltk::{RandomNumberGenerator, RGB};
+use serde::{Deserialize, Serialize};
+
+/// The look and inhabitants of a level. Each theme has its own builder recipe (see
+/// `map_builders::random_builder`), palette and spawn table (see `spawner::themed_table`).
+#[derive(PartialEq, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum LevelTheme {
+    #[default]
+    Dungeon,
+    Crypt,
+    FungalCave,
+    FloodedRuins,
+    ArcaneLibrary,
+}
+
+const THEMES: [LevelTheme; 5] = [
+    LevelTheme::Dungeon,
+    LevelTheme::Crypt,
+    LevelTheme::FungalCave,
+    LevelTheme::FloodedRuins,
+    LevelTheme::ArcaneLibrary,
+];
+
+/// Base colours for a theme's tiles. Vault walls, stairs and liquids keep their own colours
+/// everywhere.
+pub struct Palette {
+    pub floor: RGB,
+    pub earth_wall: RGB,
+    pub stone_wall: RGB,
+    pub grass: RGB,
+}
+
+impl LevelTheme {
+    /// Picks one of the themes deep enough to turn up at `depth`, each as likely as the others.
+    pub fn for_depth(depth: i32, rng: &mut RandomNumberGenerator) -> LevelTheme {
+        let available: Vec<LevelTheme> = THEMES
+            .iter()
+            .copied()
+            .filter(|theme| theme.min_depth() <= depth)
+            .collect();
+        available[rng.range(0, available.len() as i32) as usize]
+    }
+
+    /// The shallowest depth the theme can appear at.
+    pub fn min_depth(self) -> i32 {
+        match self {
+            LevelTheme::Dungeon => 1,
+            LevelTheme::FungalCave => 2,
+            LevelTheme::FloodedRuins => 3,
+            LevelTheme::Crypt => 4,
+            LevelTheme::ArcaneLibrary => 6,
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            LevelTheme::Dungeon => Palette {
+                floor: RGB::from_f32(0.5, 0.5, 0.5),
+                earth_wall: RGB::from_f32(0.6, 0.8, 0.2),
+                stone_wall: RGB::from_f32(0., 1.0, 0.),
+                grass: RGB::from_f32(0.2, 0.8, 0.2),
+            },
+            LevelTheme::Crypt => Palette {
+                floor: RGB::from_f32(0.45, 0.4, 0.35),
+                earth_wall: RGB::from_f32(0.55, 0.5, 0.4),
+                stone_wall: RGB::from_f32(0.85, 0.82, 0.7),
+                grass: RGB::from_f32(0.4, 0.5, 0.3),
+            },
+            LevelTheme::FungalCave => Palette {
+                floor: RGB::from_f32(0.45, 0.4, 0.5),
+                earth_wall: RGB::from_f32(0.55, 0.35, 0.65),
+                stone_wall: RGB::from_f32(0.65, 0.45, 0.75),
+                grass: RGB::from_f32(0.85, 0.4, 0.95),
+            },
+            LevelTheme::FloodedRuins => Palette {
+                floor: RGB::from_f32(0.4, 0.5, 0.5),
+                earth_wall: RGB::from_f32(0.35, 0.55, 0.45),
+                stone_wall: RGB::from_f32(0.3, 0.65, 0.65),
+                grass: RGB::from_f32(0.2, 0.6, 0.4),
+            },
+            LevelTheme::ArcaneLibrary => Palette {
+                floor: RGB::from_f32(0.55, 0.45, 0.3),
+                earth_wall: RGB::from_f32(0.65, 0.35, 0.15),
+                stone_wall: RGB::from_f32(0.75, 0.55, 0.9),
+                grass: RGB::from_f32(0.3, 0.7, 0.3),
+            },
+        }
+    }
+
+    /// Logged when the player first arrives on a level of this theme.
+    pub fn flavour(self) -> &'static str {
+        match self {
+            LevelTheme::Dungeon => "Rough-hewn corridors stretch away into the dark.",
+            LevelTheme::Crypt => "The air is cold and still, and smells of old bones.",
+            LevelTheme::FungalCave => "Pale fungus carpets the cave, glistening wetly.",
+            LevelTheme::FloodedRuins => "Water laps at crumbling walls; these halls have sunk.",
+            LevelTheme::ArcaneLibrary => "Dusty shelves tower overhead, and the books whisper.",
+        }
+    }
+}
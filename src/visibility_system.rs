@@ -1,6 +1,7 @@
 use rltk::{field_of_view, Point};
 use specs::prelude::*;
 
+use super::lighting_system::is_lit;
 use super::{Map, Player, Position, Viewshed};
 
 pub struct VisibilitySystem {}
@@ -10,7 +11,7 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
-        WriteStorage<'a, Position>,
+        ReadStorage<'a, Position>,
         ReadStorage<'a, Player>,
     );
 
@@ -29,6 +30,12 @@ impl<'a> System<'a> for VisibilitySystem {
 
                 let p: Option<&Player> = player.get(ent);
                 if let Some(_p) = p {
+                    // The player can only make out lit tiles, and whatever is right next to them
+                    let origin = Point::new(pos.x, pos.y);
+                    viewshed.visible_tiles.retain(|p| {
+                        is_lit(&map, map.xy_idxi32(p.x, p.y))
+                            || rltk::DistanceAlg::Pythagoras.distance2d(origin, *p) < 1.5
+                    });
                     for t in map.visible_tiles.iter_mut() { *t = false };
                     for vis in viewshed.visible_tiles.iter() {
                         let idx = map.xy_idxi32(vis.x, vis.y);
@@ -0,0 +1,138 @@
+use rltk::{Point, VirtualKeyCode};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use super::{
+    gamelog::GameLog, spawner, spells, tile_walkable, KnownSpells, Map, Position, RunState,
+    Viewshed,
+};
+
+/// Debug cheats. `used` is never reset during a run, so saves and the game over screen can
+/// tell that wizard mode was switched on at some point.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct WizardMode {
+    pub enabled: bool,
+    pub god_mode: bool,
+    pub used: bool,
+}
+
+/// F12 toggles wizard mode, the other function keys only work while it is enabled.
+pub fn wizard_input(ecs: &mut World, key: VirtualKeyCode) -> Option<RunState> {
+    if key == VirtualKeyCode::F12 {
+        let mut wizard = ecs.write_resource::<WizardMode>();
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        wizard.enabled = !wizard.enabled;
+        if wizard.enabled {
+            wizard.used = true;
+            gamelog
+                .entries
+                .push("Wizard mode enabled, this run will be flagged.".to_string());
+        } else {
+            gamelog.entries.push("Wizard mode disabled.".to_string());
+        }
+        return Some(RunState::AwaitingInput);
+    }
+
+    if !ecs.fetch::<WizardMode>().enabled {
+        return None;
+    }
+
+    match key {
+        VirtualKeyCode::F1 => {
+            reveal_map(ecs);
+            ecs.fetch_mut::<GameLog>()
+                .entries
+                .push("The whole level is revealed to you.".to_string());
+            Some(RunState::AwaitingInput)
+        }
+        VirtualKeyCode::F2 => {
+            let message = if toggle_god_mode(ecs) {
+                "God mode enabled."
+            } else {
+                "God mode disabled."
+            };
+            ecs.fetch_mut::<GameLog>().entries.push(message.to_string());
+            Some(RunState::AwaitingInput)
+        }
+        VirtualKeyCode::F3 => Some(RunState::WizardTeleport),
+        VirtualKeyCode::F4 => Some(RunState::WizardSpawn),
+        VirtualKeyCode::F5 => Some(RunState::WizardGrantSpell),
+        VirtualKeyCode::F6 => {
+            let depth = ecs.fetch::<Map>().depth;
+            Some(RunState::WizardGotoDepth { depth })
+        }
+        _ => None,
+    }
+}
+
+pub fn reveal_map(ecs: &mut World) {
+    let mut map = ecs.fetch_mut::<Map>();
+    for tile in map.revealed_tiles.iter_mut() {
+        *tile = true;
+    }
+}
+
+/// Flips god mode and returns the new setting.
+pub fn toggle_god_mode(ecs: &mut World) -> bool {
+    let mut wizard = ecs.write_resource::<WizardMode>();
+    wizard.god_mode = !wizard.god_mode;
+    wizard.god_mode
+}
+
+/// Moves the player to `target`, as long as it is an open tile on the map.
+pub fn teleport_player(ecs: &mut World, target: Point) -> bool {
+    {
+        let map = ecs.fetch::<Map>();
+        if target.x < 0 || target.x >= map.width || target.y < 0 || target.y >= map.height {
+            return false;
+        }
+        let idx = map.xy_idxi32(target.x, target.y);
+        if !tile_walkable(map.tiles[idx]) || map.blocked[idx] {
+            return false;
+        }
+    }
+
+    let player_entity = *ecs.fetch::<Entity>();
+    if let Some(pos) = ecs.write_storage::<Position>().get_mut(player_entity) {
+        pos.x = target.x;
+        pos.y = target.y;
+    }
+    if let Some(vs) = ecs.write_storage::<Viewshed>().get_mut(player_entity) {
+        vs.dirty = true;
+    }
+    *ecs.write_resource::<Point>() = target;
+    true
+}
+
+pub fn spawn_at(ecs: &mut World, name: &str, target: Point) -> Option<Entity> {
+    spawner::spawn_named_entity(ecs, name, target.x, target.y)
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum GrantSpellResult {
+    Learned,
+    AlreadyKnown,
+    NoSuchSpell,
+}
+
+/// Adds the spell called `name` to the player's `KnownSpells`, unless it is already known.
+pub fn grant_spell(ecs: &mut World, name: &str) -> GrantSpellResult {
+    let player_entity = *ecs.fetch::<Entity>();
+    let already_known = ecs
+        .read_storage::<KnownSpells>()
+        .get(player_entity)
+        .is_some_and(|known| known.spells.iter().any(|s| s.name == name));
+    if already_known {
+        return GrantSpellResult::AlreadyKnown;
+    }
+
+    match spells::learn_spell(ecs, name) {
+        None => GrantSpellResult::NoSuchSpell,
+        Some(spell) => {
+            if let Some(known) = ecs.write_storage::<KnownSpells>().get_mut(player_entity) {
+                known.spells.push(spell);
+            }
+            GrantSpellResult::Learned
+        }
+    }
+}